@@ -0,0 +1,30 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rust::tokenizer;
+use std::io::Cursor;
+
+const SOURCE: &str = include_str!("../examples/sort_3.cpp2");
+
+/// Lexes the example program repeated 1 to 1000 times. Lexing is linear when
+/// the reported throughput stays the same for every size.
+fn tokenize(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tokenize");
+    for repetitions in [1, 10, 100, 1000] {
+        let source = SOURCE.repeat(repetitions);
+        group.throughput(Throughput::Bytes(source.len() as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(repetitions),
+            &source,
+            |b, source| {
+                b.iter(|| {
+                    tokenizer::tokenize(Cursor::new(source.as_bytes()))
+                        .map(Result::unwrap)
+                        .count()
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, tokenize);
+criterion_main!(benches);
@@ -1,5 +1,8 @@
+use crate::diagnostics::Diagnostic;
 use crate::evaluator;
+use crate::evaluator::ModuleSymbols;
 use crate::parser;
+use crate::repl;
 use crate::tokenizer;
 use clap::{Parser, Subcommand};
 use serde::Deserialize;
@@ -9,9 +12,17 @@ use std::path::PathBuf;
 
 #[derive(Subcommand, Clone, Debug)]
 pub enum Command {
-    New { name: String },
+    New {
+        name: String,
+    },
     Build,
     Run,
+    /// Runs a .cpp2 file (src/main.cpp2 by default) without a C++ toolchain
+    Interpret {
+        file: Option<PathBuf>,
+    },
+    /// Starts an interactive session backed by the interpreter
+    Repl,
 }
 
 #[derive(Deserialize)]
@@ -55,7 +66,11 @@ set(CMAKE_CXX_STANDARD 23)
 set(CMAKE_CXX_STANDARD_REQUIRED ON)
 set(CMAKE_CXX_EXTENSIONS OFF)
 
-add_executable(${{CMAKE_PROJECT_NAME}} main.cpp)
+file(GLOB SOURCES CONFIGURE_DEPENDS ${{CMAKE_CURRENT_SOURCE_DIR}}/*.cpp)
+file(GLOB HEADERS CONFIGURE_DEPENDS ${{CMAKE_CURRENT_SOURCE_DIR}}/*.h)
+
+add_executable(${{CMAKE_PROJECT_NAME}} ${{SOURCES}} ${{HEADERS}})
+target_include_directories(${{CMAKE_PROJECT_NAME}} PRIVATE ${{CMAKE_CURRENT_SOURCE_DIR}})
 "#,
                         name
                     )
@@ -158,33 +173,16 @@ name = "{}"
             })
             .collect::<Vec<_>>();
 
+        // Every module is parsed before any is checked, so that calls into
+        // the namespaces of other files can be resolved.
+        let mut modules = Vec::new();
         for file in files {
-            match std::fs::File::open(&file) {
-                Ok(file_handle) => {
-                    let reader = std::io::BufReader::new(file_handle);
-                    match tokenizer::tokenize(reader) {
-                        Ok(tokens) => match parser::parse(tokens) {
-                            Some(ast) => {
-                                let output = evaluator::cpptranspile(&ast);
-                                let file_name = file.file_name().unwrap().to_str().unwrap();
-                                let new_file_path =
-                                    format!("./cpp/{}.cpp", file_name.trim_end_matches(".cpp2"));
-                                match std::fs::File::create(&new_file_path) {
-                                    Ok(mut new_file) => {
-                                        if let Err(error) = new_file.write_all(output.as_bytes()) {
-                                            println!("Failed to write to file: {}", error);
-                                        }
-                                    }
-                                    Err(error) => {
-                                        println!("Failed to create file: {}", error);
-                                    }
-                                }
-                            }
-                            None => (),
-                        },
-                        Err(error) => {
-                            println!("{}", error);
-                        }
+            match fs::read_to_string(&file) {
+                Ok(source) => {
+                    let file_name = file.display().to_string();
+                    if let Some(ast) = parse_source(&file_name, &source) {
+                        let module_name = file.file_stem().unwrap().to_str().unwrap().to_string();
+                        modules.push((module_name, file_name, source, ast));
                     }
                 }
                 Err(error) => {
@@ -192,6 +190,50 @@ name = "{}"
                 }
             }
         }
+        let symbols: Vec<ModuleSymbols> = modules
+            .iter()
+            .map(|(module_name, _, _, ast)| ModuleSymbols::collect(ast, module_name))
+            .collect();
+
+        for (module_name, file_name, source, ast) in &modules {
+            if !check_source(ast, &symbols, file_name, source) {
+                continue;
+            }
+            let (module, diagnostics) = evaluator::cpptranspile_module(ast, module_name, &symbols);
+            report(&diagnostics, file_name, source);
+            Self::write_output(&format!("./cpp/{}.h", module_name), &module.header);
+            Self::write_output(&format!("./cpp/{}.cpp", module_name), &module.source);
+        }
+    }
+
+    fn write_output(path: &str, contents: &str) {
+        match std::fs::File::create(path) {
+            Ok(mut new_file) => {
+                if let Err(error) = new_file.write_all(contents.as_bytes()) {
+                    println!("Failed to write to file: {}", error);
+                }
+            }
+            Err(error) => {
+                println!("Failed to create file: {}", error);
+            }
+        }
+    }
+
+    fn interpret(file: &Option<PathBuf>) {
+        let file = file
+            .clone()
+            .unwrap_or_else(|| PathBuf::from("./src/main.cpp2"));
+        let source = match fs::read_to_string(&file) {
+            Ok(source) => source,
+            Err(error) => {
+                eprintln!("Failed to open file: {}", error);
+                std::process::exit(1);
+            }
+        };
+        match analyze(&file.display().to_string(), &source) {
+            Some(ast) => std::process::exit(evaluator::run(&ast)),
+            None => std::process::exit(1),
+        }
     }
 
     fn run() {
@@ -237,7 +279,69 @@ name = "{}"
             Command::New { name } => Command::new(&name),
             Command::Build => Command::build(),
             Command::Run => Command::run(),
+            Command::Interpret { file } => Command::interpret(file),
+            Command::Repl => repl::start(),
+        }
+    }
+}
+
+/// Tokenizes, parses, resolves and type checks a source file, reporting
+/// every diagnostic. Returns the AST only if there were no errors.
+fn analyze(file_name: &str, source: &str) -> Option<parser::Ast> {
+    let ast = parse_source(file_name, source)?;
+    if check_source(&ast, &[], file_name, source) {
+        Some(ast)
+    } else {
+        None
+    }
+}
+
+/// Tokenizes and parses a source file, reporting every diagnostic.
+fn parse_source(file_name: &str, source: &str) -> Option<parser::Ast> {
+    let tokens = match tokenizer::tokenize(std::io::Cursor::new(source.as_bytes()))
+        .collect::<Result<Vec<_>, _>>()
+    {
+        Ok(tokens) => tokens,
+        Err(error) => {
+            report(&[Diagnostic::from(&error)], file_name, source);
+            return None;
+        }
+    };
+    let ast = match parser::parse(tokens) {
+        Ok(ast) => {
+            report(&ast.warnings, file_name, source);
+            ast
         }
+        Err(errors) => {
+            let diagnostics: Vec<Diagnostic> = errors.iter().map(Diagnostic::from).collect();
+            report(&diagnostics, file_name, source);
+            return None;
+        }
+    };
+    Some(ast)
+}
+
+/// Resolves and type checks a parsed module of a project made of `modules`,
+/// reporting every diagnostic. Returns whether there were no errors.
+fn check_source(
+    ast: &parser::Ast,
+    modules: &[ModuleSymbols],
+    file_name: &str,
+    source: &str,
+) -> bool {
+    let resolved = evaluator::resolve_with_modules(ast, modules);
+    report(&resolved, file_name, source);
+    if resolved.iter().any(Diagnostic::is_error) {
+        return false;
+    }
+    let checked = evaluator::check(ast);
+    report(&checked, file_name, source);
+    !checked.iter().any(Diagnostic::is_error)
+}
+
+fn report(diagnostics: &[Diagnostic], file_name: &str, source: &str) {
+    for diagnostic in diagnostics {
+        eprintln!("{}", diagnostic.render(file_name, source));
     }
 }
 
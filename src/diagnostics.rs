@@ -0,0 +1,289 @@
+use crate::parser::{ASTExpression, ParseError};
+use crate::tokenizer::{self, Position, TokenInfo};
+use std::fmt;
+
+const TAB_WIDTH: usize = 4;
+
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+}
+
+impl Span {
+    pub fn new(start: Position, end: Position) -> Self {
+        Self { start, end }
+    }
+
+    pub fn from_token(token: &TokenInfo) -> Self {
+        Self::new(token.start_position, token.end_position)
+    }
+
+    pub fn from_tokens(first: &TokenInfo, last: &TokenInfo) -> Self {
+        Self::new(first.start_position, last.end_position)
+    }
+
+    pub fn from_expression(expression: &ASTExpression) -> Self {
+        match (expression.first_token(), expression.last_token()) {
+            (Some(first), Some(last)) => Self::from_tokens(first, last),
+            _ => Self::default(),
+        }
+    }
+
+    fn is_known(&self) -> bool {
+        self.start.row != 0
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label {
+    pub span: Span,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub primary: Label,
+    pub secondary: Vec<Label>,
+    pub notes: Vec<String>,
+    pub help: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, message: &str, span: Span) -> Self {
+        Self {
+            severity,
+            message: message.to_string(),
+            primary: Label {
+                span,
+                message: None,
+            },
+            secondary: Vec::new(),
+            notes: Vec::new(),
+            help: None,
+        }
+    }
+
+    pub fn error(message: &str, span: Span) -> Self {
+        Self::new(Severity::Error, message, span)
+    }
+
+    pub fn warning(message: &str, span: Span) -> Self {
+        Self::new(Severity::Warning, message, span)
+    }
+
+    pub fn with_label(mut self, label: &str) -> Self {
+        self.primary.message = Some(label.to_string());
+        self
+    }
+
+    pub fn with_secondary(mut self, span: Span, label: &str) -> Self {
+        self.secondary.push(Label {
+            span,
+            message: Some(label.to_string()),
+        });
+        self
+    }
+
+    pub fn with_note(mut self, note: &str) -> Self {
+        self.notes.push(note.to_string());
+        self
+    }
+
+    pub fn with_help(mut self, help: &str) -> Self {
+        self.help = Some(help.to_string());
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    pub fn span(&self) -> Span {
+        self.primary.span
+    }
+
+    /// Renders the diagnostic rustc-style: a header, the `file:line:col`
+    /// location and the offending source lines with the spans underlined.
+    pub fn render(&self, file_name: &str, source: &str) -> String {
+        let mut output = format!("{}: {}\n", self.severity, self.message);
+
+        let mut labels: Vec<(&Label, char)> = Vec::new();
+        if self.primary.span.is_known() {
+            labels.push((&self.primary, '^'));
+        }
+        for label in &self.secondary {
+            if label.span.is_known() {
+                labels.push((label, '-'));
+            }
+        }
+        labels.sort_by_key(|(label, _)| (label.span.start.row, label.span.start.col));
+
+        let last_row = labels
+            .iter()
+            .map(|(label, _)| label.span.start.row)
+            .max()
+            .unwrap_or(0);
+        let gutter = " ".repeat(last_row.to_string().len());
+
+        if self.primary.span.is_known() {
+            output.push_str(&format!(
+                "{}--> {}:{}:{}\n",
+                gutter, file_name, self.primary.span.start.row, self.primary.span.start.col
+            ));
+        } else {
+            output.push_str(&format!("{}--> {}\n", gutter, file_name));
+        }
+
+        if !labels.is_empty() {
+            output.push_str(&format!("{} |\n", gutter));
+        }
+        let lines: Vec<&str> = source.lines().collect();
+        for (label, marker) in labels {
+            let row = label.span.start.row;
+            let line = lines
+                .get(row as usize - 1)
+                .map(|line| line.replace('\t', &" ".repeat(TAB_WIDTH)))
+                .unwrap_or_default();
+            let start = label.span.start.col.max(1) as usize - 1;
+            let end = if label.span.end.row == row {
+                label.span.end.col.max(1) as usize - 1
+            } else {
+                line.chars().count()
+            };
+            let width = end.saturating_sub(start).max(1);
+
+            output.push_str(&format!("{:>w$} | {}\n", row, line, w = gutter.len()));
+            output.push_str(&format!(
+                "{} | {}{}",
+                gutter,
+                " ".repeat(start),
+                marker.to_string().repeat(width)
+            ));
+            if let Some(message) = &label.message {
+                output.push_str(&format!(" {}", message));
+            }
+            output.push('\n');
+        }
+
+        for note in &self.notes {
+            output.push_str(&format!("{} = note: {}\n", gutter, note));
+        }
+        if let Some(help) = &self.help {
+            output.push_str(&format!("{} = help: {}\n", gutter, help));
+        }
+
+        output
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let span = self.primary.span;
+        if span.is_known() {
+            write!(
+                f,
+                "{}: {} at {}:{}",
+                self.severity, self.message, span.start.row, span.start.col
+            )
+        } else {
+            write!(f, "{}: {}", self.severity, self.message)
+        }
+    }
+}
+
+impl From<&tokenizer::Error> for Diagnostic {
+    fn from(error: &tokenizer::Error) -> Self {
+        match error {
+            tokenizer::Error::NotAKeyword(lexeme) => {
+                Diagnostic::error(&format!("not a keyword `{}`", lexeme), Span::default())
+            }
+            tokenizer::Error::InvalidPattern(lexeme, position) => {
+                let end = position.advance(lexeme);
+                Diagnostic::error(
+                    &format!("invalid pattern `{}`", lexeme),
+                    Span::new(*position, end),
+                )
+                .with_label("not a valid token")
+            }
+            tokenizer::Error::InvalidStream => {
+                Diagnostic::error("invalid stream, cannot read", Span::default())
+            }
+            tokenizer::Error::InvalidUtf8(position) => Diagnostic::error(
+                "stream did not contain valid UTF-8",
+                Span::new(*position, *position),
+            ),
+            tokenizer::Error::UnclosedString(position) => {
+                let end = position.advance("\"");
+                Diagnostic::error("unclosed string", Span::new(*position, end))
+                    .with_label("string starts here")
+                    .with_help("add a closing `\"`")
+            }
+            tokenizer::Error::UnclosedComment(position) => {
+                let end = position.advance("/*");
+                Diagnostic::error("unclosed block comment", Span::new(*position, end))
+                    .with_label("comment starts here")
+                    .with_help("add a closing `*/`")
+            }
+            tokenizer::Error::InvalidEscape(sequence, position) => {
+                let end = position.advance(sequence);
+                Diagnostic::error(
+                    &format!("unknown escape sequence `{}`", sequence),
+                    Span::new(*position, end),
+                )
+                .with_label("not a valid escape")
+                .with_help("valid escapes are `\\n`, `\\t`, `\\r`, `\\0`, `\\\\`, `\\\"`, `\\'` and `\\u{...}`")
+            }
+            tokenizer::Error::InvalidNumber(lexeme, position) => {
+                let end = position.advance(lexeme);
+                Diagnostic::error(
+                    &format!("invalid number literal `{}`", lexeme),
+                    Span::new(*position, end),
+                )
+                .with_label("malformed, or out of range for its type")
+            }
+            tokenizer::Error::InvalidChar(lexeme, position) => {
+                let end = position.advance(lexeme);
+                Diagnostic::error(
+                    &format!("invalid char literal `{}`", lexeme),
+                    Span::new(*position, end),
+                )
+                .with_label("a char literal holds exactly one character")
+                .with_help("use double quotes for a string")
+            }
+        }
+    }
+}
+
+impl From<&ParseError> for Diagnostic {
+    fn from(error: &ParseError) -> Self {
+        let expected = error.expected_description();
+        let found = if error.found.lexeme.is_empty() {
+            format!("{:?}", error.found.token)
+        } else {
+            format!("`{}`", error.found.lexeme)
+        };
+        Diagnostic::error(
+            &format!("expected {}, found {}", expected, found),
+            Span::from_token(&error.found),
+        )
+        .with_label(&format!("expected {}", expected))
+    }
+}
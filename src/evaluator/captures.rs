@@ -0,0 +1,168 @@
+use crate::parser::visitor::ASTVisitor;
+use crate::parser::*;
+use std::collections::HashMap;
+
+/// Works out which variables of enclosing functions each anonymous function
+/// uses, so that the transpiler can list them in the capture list of the
+/// lambda it emits.
+pub struct ASTCaptureAnalyzer {
+    // Whether each variable in scope is `mut`, innermost scope last. The
+    // outermost scope holds the globals, which a lambda uses without
+    // capturing them.
+    scopes: Vec<HashMap<String, bool>>,
+    // The anonymous functions being analyzed, innermost last, each with the
+    // number of scopes outside of it and what it captured so far.
+    closures: Vec<(usize, Vec<ASTCapture>)>,
+}
+
+impl ASTCaptureAnalyzer {
+    pub fn new() -> Self {
+        Self {
+            scopes: vec![HashMap::new()],
+            closures: Vec::new(),
+        }
+    }
+
+    fn enter_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    fn exit_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    fn declare(&mut self, name: &str, is_mut: bool) {
+        self.scopes
+            .last_mut()
+            .unwrap()
+            .insert(name.to_string(), is_mut);
+    }
+
+    /// Records a use of `name` as a capture of every anonymous function
+    /// between the use and the variable's declaration.
+    fn use_variable(&mut self, name: &str) {
+        let (scope, is_mut) = match self
+            .scopes
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(index, scope)| scope.get(name).map(|is_mut| (index, *is_mut)))
+        {
+            Some((0, _)) | None => return,
+            Some(found) => found,
+        };
+        for (depth, captures) in &mut self.closures {
+            if *depth > scope && !captures.iter().any(|capture| capture.identifier == name) {
+                captures.push(ASTCapture {
+                    identifier: name.to_string(),
+                    by_reference: is_mut,
+                });
+            }
+        }
+    }
+
+    fn visit_function(&mut self, parameters: &[FuncDeclParameter], body: &ASTStatement) {
+        self.enter_scope();
+        for parameter in parameters {
+            self.declare(&parameter.identifier.lexeme, parameter.is_mut);
+        }
+        self.visit_statement(body);
+        self.exit_scope();
+    }
+}
+
+impl ASTVisitor<'_> for ASTCaptureAnalyzer {
+    fn visit_func_decl_statement(&mut self, func_decl_statement: &ASTFuncDeclStatement) {
+        self.visit_function(&func_decl_statement.parameters, &func_decl_statement.body);
+    }
+
+    fn visit_closure_expression(&mut self, closure_expression: &ASTClosureExpression) {
+        self.closures.push((self.scopes.len(), Vec::new()));
+        self.visit_function(&closure_expression.parameters, &closure_expression.body);
+        let (_, captures) = self.closures.pop().unwrap();
+        *closure_expression.captures.borrow_mut() = captures;
+    }
+
+    fn visit_block_statement(&mut self, block_statement: &ASTBlockStatement) {
+        self.enter_scope();
+        for statement in &block_statement.statements {
+            self.visit_statement(statement);
+        }
+        self.exit_scope();
+    }
+
+    fn visit_for_statement(&mut self, for_statement: &ASTForStatement) {
+        self.visit_expression(&for_statement.iterable);
+        self.enter_scope();
+        self.declare(&for_statement.identifier.lexeme, false);
+        self.visit_statement(&for_statement.body);
+        self.exit_scope();
+    }
+
+    fn visit_match_statement(&mut self, match_statement: &ASTMatchStatement) {
+        self.visit_expression(&match_statement.scrutinee);
+        for arm in &match_statement.arms {
+            self.enter_scope();
+            match &arm.pattern {
+                ASTMatchPattern::Literal(expr) => self.visit_expression(expr),
+                ASTMatchPattern::Range(expr) => self.visit_range_expression(expr),
+                ASTMatchPattern::Binding(identifier) => self.declare(&identifier.lexeme, false),
+                ASTMatchPattern::Wildcard(_) => {}
+            }
+            self.visit_statement(&arm.body);
+            self.exit_scope();
+        }
+        if let Some(else_branch) = &match_statement.else_branch {
+            self.visit_statement(&else_branch.else_statement);
+        }
+    }
+
+    fn visit_let_statement(&mut self, let_statement: &ASTLetStatement) {
+        self.visit_expression(&let_statement.initializer);
+        self.declare(&let_statement.identifier.lexeme, let_statement.is_mut);
+    }
+
+    fn visit_call_expression(&mut self, call_expression: &ASTCallExpression) {
+        if call_expression.qualifier.is_empty() {
+            self.use_variable(&call_expression.identifier.lexeme);
+        }
+        for argument in &call_expression.arguments {
+            self.visit_expression(argument);
+        }
+    }
+
+    fn visit_assignment_expression(&mut self, assignment_expression: &ASTAssignmentExpression) {
+        self.use_variable(&assignment_expression.identifier.lexeme);
+        self.visit_expression(&assignment_expression.expression);
+    }
+
+    fn visit_array_assignment_expression(
+        &mut self,
+        array_assignment_expression: &ASTArrayAssignmentExpression,
+    ) {
+        self.visit_expression(&array_assignment_expression.index_expression);
+        self.visit_expression(&array_assignment_expression.expression);
+    }
+
+    fn visit_variable_expression(&mut self, variable_expression: &ASTVariableExpression) {
+        self.use_variable(variable_expression.identifier());
+    }
+
+    fn visit_number_expression(&mut self, _number: &ASTNumberExpression) {}
+
+    fn visit_string_expression(&mut self, _string: &ASTStringExpression) {}
+
+    fn visit_boolean_expression(&mut self, _boolean: &ASTBooleanExpression) {}
+
+    fn visit_unary_expression(&mut self, unary_expression: &ASTUnaryExpression) {
+        self.visit_expression(&unary_expression.operand);
+    }
+
+    fn visit_type_annotation_expression(
+        &mut self,
+        _type_annotation_expression: &ASTTypeAnnotationExpression,
+    ) {
+    }
+
+    fn finalize(&mut self) {}
+}
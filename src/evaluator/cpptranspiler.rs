@@ -1,6 +1,10 @@
+use crate::diagnostics::{Diagnostic, Span};
+use crate::evaluator::symbols::{self, ModuleSymbols};
 use crate::evaluator::to_cpp;
+use crate::evaluator::types::{Float, Int, Type};
 use crate::parser::visitor::ASTVisitor;
 use crate::parser::*;
+use crate::tokenizer::{NumberValue, Token, TokenInfo};
 use std::collections::HashMap;
 
 pub struct ASTCppTranspiler {
@@ -8,12 +12,35 @@ pub struct ASTCppTranspiler {
     types: HashMap<String, to_cpp::TypeInfo>,
     std_names: HashMap<String, String>,
     includes: Vec<String>,
+    structs: HashMap<String, Vec<String>>,
+    output: Output,
+    namespace: Option<String>,
+    // `main` can not be inside a namespace, so in a file with one it is
+    // emitted after the namespace is closed.
+    main: String,
+    module: String,
+    modules: Vec<ModuleSymbols>,
+    // Headers of other modules of the project this one uses.
+    module_includes: Vec<String>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// What part of a module the transpiler emits.
+pub enum Output {
+    /// Everything in one translation unit.
+    Combined,
+    /// The struct definitions and function prototypes, wrapped in the
+    /// include guard with the given name.
+    Header(String),
+    /// The function definitions and remaining statements, which include the
+    /// header with the given file name.
+    Source(String),
 }
 
 #[derive(PartialEq)]
 enum AfterIdentifier {
     None,
-    Array
+    Array,
 }
 
 impl ASTCppTranspiler {
@@ -38,11 +65,56 @@ impl ASTCppTranspiler {
     }
 
     pub fn new() -> Self {
+        Self::with_output(Output::Combined)
+    }
+
+    pub fn with_output(output: Output) -> Self {
         Self {
             result: String::new(),
             types: to_cpp::init_types().unwrap(),
             std_names: to_cpp::init_std_names().unwrap(),
             includes: Vec::new(),
+            structs: HashMap::new(),
+            output,
+            namespace: None,
+            main: String::new(),
+            module: String::new(),
+            modules: Vec::new(),
+            module_includes: Vec::new(),
+            diagnostics: Vec::new(),
+        }
+    }
+
+    /// Lets the module `module` use what the other `modules` of the project
+    /// declare, including their headers where it does.
+    pub fn declare_modules(&mut self, module: &str, modules: &[ModuleSymbols]) {
+        self.module = module.to_string();
+        self.modules = modules.to_vec();
+    }
+
+    fn add_module_include(&mut self, module: Option<&ModuleSymbols>) {
+        let header = match module {
+            Some(module) if module.module != self.module => format!("{}.h", module.module),
+            _ => return,
+        };
+        if !self.module_includes.contains(&header) {
+            self.module_includes.push(header);
+        }
+    }
+
+    /// The namespace a name with the given qualifier is looked up in.
+    fn qualified_namespace(&self, qualifier: &[TokenInfo]) -> String {
+        if qualifier.is_empty() {
+            self.namespace.clone().unwrap_or_default()
+        } else {
+            path_name(qualifier)
+        }
+    }
+
+    fn add_qualifier(&mut self, qualifier: &[TokenInfo]) {
+        if !qualifier.is_empty() {
+            self.add_text(&path_name(qualifier));
+            self.add_text("::");
         }
     }
 
@@ -50,45 +122,59 @@ impl ASTCppTranspiler {
         let mut after_identifier = AfterIdentifier::None;
         if let Some(t) = type_annotation {
             match &t.kind {
+                ASTExpressionKind::TypeAnnotation(expr) if !expr.qualifier.is_empty() => {
+                    let namespace = path_name(&expr.qualifier);
+                    let module =
+                        symbols::struct_module(&self.modules, &namespace, &expr.base.lexeme)
+                            .cloned();
+                    self.add_module_include(module.as_ref());
+                    self.add_qualifier(&expr.qualifier);
+                    self.add_text(&expr.base.lexeme);
+                }
                 ASTExpressionKind::TypeAnnotation(expr) => {
                     if let Some(cpp_t) = to_cpp::translate_type(&expr.base, &self.types) {
-                        if &cpp_t.name == "Array"  {
+                        if &cpp_t.name == "Array" {
                             after_identifier = AfterIdentifier::Array;
                         } else {
                             self.add_text(&cpp_t.name);
                         }
 
-                        if !self.includes.contains(&cpp_t.library) {
-                            self.includes.push(cpp_t.library);
-                        }
+                        self.add_include(&cpp_t.library);
                     } else {
-                        if &expr.base.lexeme == "Array"  {
+                        if &expr.base.lexeme == "Array" {
                             after_identifier = AfterIdentifier::Array;
                         } else {
+                            let namespace = self.qualified_namespace(&[]);
+                            let module = symbols::struct_module(
+                                &self.modules,
+                                &namespace,
+                                &expr.base.lexeme,
+                            )
+                            .cloned();
+                            self.add_module_include(module.as_ref());
                             self.add_text(&expr.base.lexeme);
                         }
                     }
 
-                    for generic in &expr.generics {
-                        if after_identifier == AfterIdentifier::None {
-                            self.add_text("<");
-                        }
-                        if let Some(cpp_t) = to_cpp::translate_type(&generic, &self.types) {
-                            self.add_text(&cpp_t.name);
-
-                            if !self.includes.contains(&cpp_t.library) {
-                                self.includes.push(cpp_t.library);
-                            }
-                        } else {
-                            self.add_text(&expr.base.lexeme);
-                        }
-
-                        if after_identifier == AfterIdentifier::None {
-                            self.add_text(">");
+                    // The element type of an array goes into the `std::vector`
+                    // that `add_value_type` wraps around it.
+                    let is_template =
+                        after_identifier == AfterIdentifier::None && !expr.generics.is_empty();
+                    if is_template {
+                        self.add_text("<");
+                    }
+                    for (i, generic) in expr.generics.iter().enumerate() {
+                        if i != 0 {
+                            self.add_text(",");
+                            self.add_whitespace();
                         }
-
+                        self.add_value_type(&Some(generic.clone()));
+                    }
+                    if is_template {
+                        self.add_text(">");
                     }
                 }
+                ASTExpressionKind::FunctionType(expr) => self.add_function_type(expr),
                 _ => {}
             }
         } else {
@@ -97,32 +183,463 @@ impl ASTCppTranspiler {
 
         return after_identifier;
     }
+
+    /// Emits a function type as a `std::function`, which holds lambdas and
+    /// plain functions alike.
+    fn add_function_type(&mut self, function_type: &ASTFunctionTypeExpression) {
+        self.add_include("functional");
+        self.add_text("std::function<");
+        match &function_type.return_type {
+            Some(return_type) => self.add_value_type(&Some(*return_type.clone())),
+            None => self.add_text("void"),
+        }
+        self.add_text("(");
+        for (i, parameter) in function_type.parameters.iter().enumerate() {
+            if i != 0 {
+                self.add_text(",");
+                self.add_whitespace();
+            }
+            self.add_value_type(&Some(parameter.clone()));
+        }
+        self.add_text(")>");
+    }
+
+    /// Emits the return type of a function. Without an annotation it is
+    /// `void` when the function returns no value and otherwise the type the
+    /// checker inferred for what it returns, so that the prototype in the
+    /// header can be called before the definition is seen.
+    fn add_return_type(&mut self, func_decl_statement: &ASTFuncDeclStatement) {
+        if func_decl_statement.type_annotation.is_some() {
+            return self.add_value_type(&func_decl_statement.type_annotation);
+        }
+        match Self::returned_value(&func_decl_statement.body) {
+            Some(value) => self.add_value_type(
+                &value
+                    .ty()
+                    .and_then(|ty| Self::inferred_type_annotation(&ty)),
+            ),
+            None => self.add_text("void"),
+        }
+    }
+
+    /// The value of the first `return` in `statement` that has one, leaving
+    /// out nested functions.
+    fn returned_value(statement: &ASTStatement) -> Option<&ASTExpression> {
+        match &statement.kind {
+            ASTStatementKind::Return(return_statement) => return_statement.return_value.as_ref(),
+            ASTStatementKind::Block(block) => {
+                block.statements.iter().find_map(Self::returned_value)
+            }
+            ASTStatementKind::If(if_statement) => Self::returned_value(&if_statement.then_branch)
+                .or_else(|| {
+                    if_statement
+                        .else_branch
+                        .as_ref()
+                        .and_then(|else_branch| Self::returned_value(&else_branch.else_statement))
+                }),
+            ASTStatementKind::While(while_statement) => Self::returned_value(&while_statement.body),
+            ASTStatementKind::For(for_statement) => Self::returned_value(&for_statement.body),
+            ASTStatementKind::Match(match_statement) => match_statement
+                .arms
+                .iter()
+                .map(|arm| arm.body.as_ref())
+                .chain(
+                    match_statement
+                        .else_branch
+                        .as_ref()
+                        .map(|else_branch| else_branch.else_statement.as_ref()),
+                )
+                .find_map(Self::returned_value),
+            _ => None,
+        }
+    }
+
+    /// Builds a type annotation for a type inferred by the type checker, so
+    /// that it can be emitted like one written in the source.
+    fn inferred_type_annotation(ty: &Type) -> Option<ASTExpression> {
+        let name = |lexeme: String| TokenInfo {
+            token: Token::Identifier,
+            lexeme,
+            ..Default::default()
+        };
+        match ty {
+            Type::Array(element) => match element.as_ref() {
+                Type::Function(_, _) => None,
+                element => Some(ASTExpression::type_annotation(
+                    name(String::from("Array")),
+                    vec![Self::inferred_type_annotation(element)?],
+                )),
+            },
+            Type::Function(_, _) | Type::Void | Type::Unknown => None,
+            ty => Some(ASTExpression::type_annotation(
+                name(ty.to_string()),
+                Vec::new(),
+            )),
+        }
+    }
+
+    fn add_include(&mut self, library: &str) {
+        if !library.is_empty() && !self.includes.iter().any(|include| include == library) {
+            self.includes.push(library.to_string());
+        }
+    }
+
+    /// Emits the type of a value that is stored or passed on its own, where
+    /// an array can not decay to a C array and becomes a `std::vector`.
+    fn add_value_type(&mut self, type_annotation: &Option<ASTExpression>) {
+        let start = self.result.len();
+        if self.add_type_annotation(type_annotation) == AfterIdentifier::Array {
+            self.result.insert_str(start, "std::vector<");
+            self.add_text(">");
+            self.add_include("vector");
+        }
+    }
+
+    fn add_struct_field(&mut self, field: &ASTStructField) {
+        self.add_value_type(&Some(field.type_annotation.clone()));
+        self.add_whitespace();
+        self.add_text(&field.identifier.lexeme);
+        self.add_text(";");
+    }
+
+    fn add_parameter(&mut self, parameter: &FuncDeclParameter) {
+        if !parameter.is_mut {
+            self.add_text("const");
+            self.add_whitespace();
+        }
+        self.add_value_type(&parameter.type_annotation);
+        self.add_whitespace();
+        self.add_text(&parameter.identifier.lexeme);
+    }
+
+    /// Emits the body of `main`, which receives its `args` from `argc` and
+    /// `argv` and returns 0 when it ends without a `return`.
+    fn add_main_body(&mut self, func_decl_statement: &ASTFuncDeclStatement) {
+        let statements = match &func_decl_statement.body.kind {
+            ASTStatementKind::Block(block) => &block.statements,
+            _ => return self.visit_statement(&func_decl_statement.body),
+        };
+        self.add_text("{");
+        self.add_whitespace();
+        if let Some(args) = func_decl_statement.parameters.first() {
+            self.add_parameter(args);
+            self.add_text("(argv, argv + argc);");
+            self.add_whitespace();
+        }
+        if let Some(namespace) = self.namespace.clone() {
+            self.add_text(&format!("using namespace {};", namespace));
+            self.add_whitespace();
+        }
+        for statement in statements {
+            self.visit_statement(statement);
+            self.add_whitespace();
+        }
+        if !matches!(
+            statements.last().map(|statement| &statement.kind),
+            Some(ASTStatementKind::Return(_))
+        ) {
+            self.add_keyword("return");
+            self.add_whitespace();
+            self.add_text("0;");
+            self.add_whitespace();
+        }
+        self.add_text("}");
+    }
+
+    fn expression_to_string(&mut self, expression: &ASTExpression) -> String {
+        let result = std::mem::take(&mut self.result);
+        self.visit_expression(expression);
+        std::mem::replace(&mut self.result, result)
+    }
+
+    /// Emits an operand, parenthesized when it binds looser than
+    /// `min_precedence` so that C++ groups it as the tree does.
+    fn add_operand(&mut self, operand: &ASTExpression, min_precedence: u8) {
+        if operand.precedence() < min_precedence {
+            self.result.push('(');
+            self.visit_expression(operand);
+            self.result.push(')');
+        } else {
+            self.visit_expression(operand);
+        }
+    }
+
+    /// Emits the value of an assignment. A chained assignment is only an
+    /// operand here, so the semicolon it ends with is dropped.
+    fn add_assigned_value(&mut self, value: &ASTExpression) {
+        self.visit_expression(value);
+        if value.precedence() == ASSIGNMENT_PRECEDENCE {
+            self.result.pop();
+        }
+    }
+
+    fn add_match_arm_body(&mut self, binding: Option<(&str, &str)>, body: &ASTStatement) {
+        self.add_text("{");
+        self.add_whitespace();
+        if let Some((identifier, value)) = binding {
+            self.add_text(&format!("const auto {} = {};", identifier, value));
+            self.add_whitespace();
+        }
+        match &body.kind {
+            ASTStatementKind::Block(block) => {
+                for statement in &block.statements {
+                    self.visit_statement(statement);
+                    self.add_whitespace();
+                }
+            }
+            _ => {
+                self.visit_statement(body);
+                self.add_whitespace();
+            }
+        }
+        self.add_text("}");
+    }
+
+    fn add_match_switch(&mut self, match_statement: &ASTMatchStatement) {
+        self.add_keyword("switch");
+        self.add_whitespace();
+        self.add_text("(");
+        self.add_whitespace();
+        self.visit_expression(&match_statement.scrutinee);
+        self.add_whitespace();
+        self.add_text(")");
+        self.add_whitespace();
+        self.add_text("{");
+        self.add_whitespace();
+        for arm in &match_statement.arms {
+            if let ASTMatchPattern::Literal(literal) = &arm.pattern {
+                self.add_keyword("case");
+                self.add_whitespace();
+                self.visit_expression(literal);
+                self.add_text(":");
+                self.add_whitespace();
+                self.add_match_arm_body(None, &arm.body);
+                self.add_whitespace();
+                self.add_keyword("break;");
+                self.add_whitespace();
+            }
+        }
+        if let Some(else_branch) = &match_statement.else_branch {
+            self.add_keyword("default:");
+            self.add_whitespace();
+            self.add_match_arm_body(None, &else_branch.else_statement);
+            self.add_whitespace();
+            self.add_keyword("break;");
+            self.add_whitespace();
+        }
+        self.add_text("}");
+    }
+
+    fn add_match_if_chain(&mut self, match_statement: &ASTMatchStatement) {
+        let is_variable = matches!(
+            match_statement.scrutinee.kind,
+            ASTExpressionKind::Variable(_)
+        );
+        let value = if is_variable {
+            self.expression_to_string(&match_statement.scrutinee)
+        } else {
+            self.add_text("{");
+            self.add_whitespace();
+            self.add_text("const auto match_value =");
+            self.add_whitespace();
+            self.visit_expression(&match_statement.scrutinee);
+            self.add_text(";");
+            self.add_whitespace();
+            String::from("match_value")
+        };
+
+        let mut is_first = true;
+        let mut is_exhaustive = false;
+        for arm in &match_statement.arms {
+            let condition = match &arm.pattern {
+                ASTMatchPattern::Literal(literal) => Some(format!(
+                    "{} == {}",
+                    value,
+                    self.expression_to_string(literal)
+                )),
+                ASTMatchPattern::Range(range) => Some(format!(
+                    "{} >= {} && {} < {}",
+                    value,
+                    self.expression_to_string(&range.start),
+                    value,
+                    self.expression_to_string(&range.end)
+                )),
+                ASTMatchPattern::Binding(_) | ASTMatchPattern::Wildcard(_) => None,
+            };
+
+            if !is_first {
+                self.add_whitespace();
+                self.add_keyword("else");
+                self.add_whitespace();
+            }
+            is_first = false;
+
+            match condition {
+                Some(condition) => {
+                    self.add_keyword("if");
+                    self.add_whitespace();
+                    self.add_text("(");
+                    self.add_whitespace();
+                    self.add_text(&condition);
+                    self.add_whitespace();
+                    self.add_text(")");
+                    self.add_whitespace();
+                    self.add_match_arm_body(None, &arm.body);
+                }
+                None => {
+                    let binding = match &arm.pattern {
+                        ASTMatchPattern::Binding(identifier) => {
+                            Some((identifier.lexeme.as_str(), value.as_str()))
+                        }
+                        _ => None,
+                    };
+                    self.add_match_arm_body(binding, &arm.body);
+                    is_exhaustive = true;
+                    break;
+                }
+            }
+        }
+
+        if !is_exhaustive {
+            if let Some(else_branch) = &match_statement.else_branch {
+                if !is_first {
+                    self.add_whitespace();
+                    self.add_keyword("else");
+                    self.add_whitespace();
+                }
+                self.add_match_arm_body(None, &else_branch.else_statement);
+            }
+        }
+
+        if !is_variable {
+            self.add_whitespace();
+            self.add_text("}");
+        }
+    }
+    /// Emits a number literal, with the sign of a negated literal inside the
+    /// braces of a fixed-width one.
+    fn add_number(&mut self, number: &ASTNumberExpression, negated: bool) {
+        // The magnitude of the minimum does not fit the type itself, and for
+        // `int64_t` no other integer type either, so it is written by name.
+        if let (NumberValue::Int(value), Some(Type::Int(int))) = (number.value, &number.suffix) {
+            if negated && int.is_signed() && value == 1 << (int.bits() - 1) {
+                self.add_text(&format!("INT{}_MIN", int.bits()));
+                self.add_include("cstdint");
+                return;
+            }
+        }
+        let literal = match number.value {
+            NumberValue::Int(value) => match number.num.lexeme.get(..2) {
+                Some("0x") => format!("0x{:x}", value),
+                Some("0b") => format!("0b{:b}", value),
+                Some("0o") => format!("0{:o}", value),
+                _ => value.to_string(),
+            },
+            NumberValue::Float(value) => format!("{:?}", value),
+        };
+        let literal = if negated {
+            format!("-{}", literal)
+        } else {
+            literal
+        };
+
+        match &number.suffix {
+            None | Some(Type::Int(Int::I32)) | Some(Type::Float(Float::F64)) => {
+                self.add_text(&literal)
+            }
+            Some(Type::Float(Float::F32)) => self.add_text(&format!("{}f", literal)),
+            Some(suffix) => match self.types.get(&suffix.to_string()).cloned() {
+                Some(cpp_type) => {
+                    self.add_text(&format!("{}{{{}}}", cpp_type.name, literal));
+                    self.add_include(&cpp_type.library);
+                }
+                None => self.add_text(&literal),
+            },
+        }
+    }
 }
 
 impl ASTVisitor<'_> for ASTCppTranspiler {
     fn visit_func_decl_statement(&mut self, func_decl_statement: &ASTFuncDeclStatement) {
-        self.add_type_annotation(&func_decl_statement.type_annotation);
+        let is_main = func_decl_statement.identifier.lexeme == "main";
+        if is_main && matches!(self.output, Output::Header(_)) {
+            return;
+        }
+        // A template has to be defined wherever it is instantiated, so a
+        // generic function lives in the header only.
+        let is_generic = !func_decl_statement.generics.is_empty();
+        if is_generic && matches!(self.output, Output::Source(_)) {
+            return;
+        }
+        let start = self.result.len();
+        if is_generic {
+            self.add_keyword("template");
+            self.add_text("<");
+            for (i, generic) in func_decl_statement.generics.iter().enumerate() {
+                if i != 0 {
+                    self.add_text(",");
+                    self.add_whitespace();
+                }
+                self.add_keyword("typename");
+                self.add_whitespace();
+                self.add_text(&generic.lexeme);
+            }
+            self.add_text(">");
+            self.add_whitespace();
+        }
+        if is_main && func_decl_statement.type_annotation.is_none() {
+            self.add_text("int");
+        } else {
+            self.add_return_type(func_decl_statement);
+        }
         self.add_whitespace();
         self.add_text(&func_decl_statement.identifier.lexeme);
-        let are_parameters_empty = func_decl_statement.parameters.is_empty();
-        if !are_parameters_empty {
-            self.add_text("(");
+        self.add_text("(");
+        if is_main && !func_decl_statement.parameters.is_empty() {
+            self.add_text("int argc, char** argv");
         } else {
-            self.add_text("()");
+            for (i, parameter) in func_decl_statement.parameters.iter().enumerate() {
+                if i != 0 {
+                    self.add_text(",");
+                    self.add_whitespace();
+                }
+                self.add_parameter(parameter);
+            }
+        }
+        self.add_text(")");
+        if matches!(self.output, Output::Header(_)) {
+            if !is_generic {
+                self.add_text(";");
+                return;
+            }
             self.add_whitespace();
+            let header = std::mem::replace(&mut self.output, Output::Combined);
+            self.visit_statement(&func_decl_statement.body);
+            self.output = header;
+            return;
         }
-        for (i, parameter) in func_decl_statement.parameters.iter().enumerate() {
-            if i != 0 {
-                self.add_text(",");
-                self.add_whitespace();
+        self.add_whitespace();
+        if is_main {
+            self.add_main_body(func_decl_statement);
+            if self.namespace.is_some() {
+                self.main = self.result.split_off(start);
             }
-            self.add_text(&parameter.identifier.lexeme);
+        } else {
+            self.visit_statement(&func_decl_statement.body);
         }
-        if !are_parameters_empty {
-            self.add_text(")");
+    }
+
+    fn visit_namespace_statement(&mut self, namespace_statement: &ASTNamespaceStatement) {
+        let namespace = namespace_statement.name();
+        self.add_keyword("namespace");
+        self.add_whitespace();
+        self.add_text(&namespace);
+        self.add_whitespace();
+        self.add_text("{");
+        if !matches!(self.output, Output::Header(_)) {
             self.add_whitespace();
         }
-        self.visit_statement(&func_decl_statement.body);
+        self.namespace = Some(namespace);
     }
 
     fn visit_for_statement(&mut self, for_statement: &ASTForStatement) {
@@ -179,8 +696,12 @@ impl ASTVisitor<'_> for ASTCppTranspiler {
     fn visit_while_statement(&mut self, while_statement: &ASTWhileStatement) {
         self.add_keyword("while");
         self.add_whitespace();
+        self.add_text("(");
+        self.add_whitespace();
         self.visit_expression(&while_statement.condition);
         self.add_whitespace();
+        self.add_text(")");
+        self.add_whitespace();
         self.visit_statement(&while_statement.body);
     }
     fn visit_block_statement(&mut self, block_statement: &ASTBlockStatement) {
@@ -212,27 +733,59 @@ impl ASTVisitor<'_> for ASTCppTranspiler {
         }
     }
 
+    fn visit_match_statement(&mut self, match_statement: &ASTMatchStatement) {
+        if match_statement.is_switchable() {
+            self.add_match_switch(match_statement);
+        } else {
+            self.add_match_if_chain(match_statement);
+        }
+    }
+
+    fn visit_struct_statement(&mut self, struct_statement: &ASTStructStatement) {
+        self.structs.insert(
+            struct_statement.identifier.lexeme.clone(),
+            struct_statement
+                .fields
+                .iter()
+                .map(|field| field.identifier.lexeme.clone())
+                .collect(),
+        );
+        if matches!(self.output, Output::Source(_)) {
+            return;
+        }
+
+        self.add_keyword("struct");
+        self.add_whitespace();
+        self.add_text(&struct_statement.identifier.lexeme);
+        self.add_whitespace();
+        self.add_text("{");
+        self.add_whitespace();
+        for field in &struct_statement.fields {
+            self.add_struct_field(field);
+            self.add_whitespace();
+        }
+        self.add_text("};");
+    }
+
     fn visit_let_statement(&mut self, let_statement: &ASTLetStatement) {
         if !let_statement.is_mut {
             self.add_text("const");
             self.add_whitespace();
         }
 
-        match self.add_type_annotation(&let_statement.type_annotation) {
-            AfterIdentifier::Array => {
-        self.add_whitespace();
-
-        self.add_text(let_statement.identifier.lexeme.as_str());
-            self.add_text("[]");
+        let type_annotation = match &let_statement.type_annotation {
+            Some(type_annotation) => Some(type_annotation.clone()),
+            None => let_statement
+                .initializer
+                .ty()
+                .and_then(|ty| Self::inferred_type_annotation(&ty)),
+        };
 
-            }
-            AfterIdentifier::None => {
+        // Arrays are `std::vector`s here too, as they are when passed to a
+        // function.
+        self.add_value_type(&type_annotation);
         self.add_whitespace();
-
         self.add_text(let_statement.identifier.lexeme.as_str());
-            }
-        }
-
 
         self.add_whitespace();
         self.add_text("=");
@@ -242,7 +795,33 @@ impl ASTVisitor<'_> for ASTCppTranspiler {
     }
 
     fn visit_statement(&mut self, statement: &ASTStatement) {
+        if let Output::Header(_) = self.output {
+            // Only top level statements are visited for a header, and only
+            // declarations belong in it.
+            if matches!(
+                statement.kind,
+                ASTStatementKind::Struct(_)
+                    | ASTStatementKind::FuncDecl(_)
+                    | ASTStatementKind::Namespace(_)
+            ) {
+                let start = self.result.len();
+                Self::do_visit_statement(self, statement);
+                if self.result.len() > start {
+                    self.result.push('\n');
+                }
+            }
+            return;
+        }
         Self::do_visit_statement(self, statement);
+        // Calls are expressions, so only a call statement ends with `;`.
+        if let ASTStatementKind::Expression(expression) = &statement.kind {
+            if matches!(
+                expression.kind,
+                ASTExpressionKind::Call(_) | ASTExpressionKind::StdCall(_)
+            ) {
+                self.add_text(";");
+            }
+        }
     }
 
     fn visit_std_call_expression(&mut self, std_call_expression: &ASTStdCallExpression) {
@@ -260,7 +839,18 @@ impl ASTVisitor<'_> for ASTCppTranspiler {
                 self.includes.push(library);
             }
         } else {
-            self.add_text(&fn_name); //unknown std function error
+            self.add_text(&fn_name);
+            self.diagnostics.push(
+                Diagnostic::warning(
+                    &format!("unknown standard library function `{}`", fn_name),
+                    Span::from_tokens(
+                        &std_call_expression.std_keyword,
+                        &std_call_expression.identifier,
+                    ),
+                )
+                .with_label("no header is known for this function")
+                .with_help("add the function and its header to functions.csv"),
+            );
         }
 
         self.add_text("(");
@@ -272,11 +862,30 @@ impl ASTVisitor<'_> for ASTCppTranspiler {
             self.visit_expression(argument);
         }
         self.add_text(")");
-        self.add_text(";");
     }
 
     fn visit_call_expression(&mut self, call_expression: &ASTCallExpression) {
+        let namespace = self.qualified_namespace(&call_expression.qualifier);
+        let module = symbols::function_module(
+            &self.modules,
+            &namespace,
+            &call_expression.identifier.lexeme,
+        )
+        .cloned();
+        self.add_module_include(module.as_ref());
+        self.add_qualifier(&call_expression.qualifier);
         self.add_text(&call_expression.identifier.lexeme);
+        if !call_expression.generic_arguments.is_empty() {
+            self.add_text("<");
+            for (i, generic_argument) in call_expression.generic_arguments.iter().enumerate() {
+                if i != 0 {
+                    self.add_text(",");
+                    self.add_whitespace();
+                }
+                self.add_value_type(&Some(generic_argument.clone()));
+            }
+            self.add_text(">");
+        }
         self.add_text("(");
         for (i, argument) in call_expression.arguments.iter().enumerate() {
             if i != 0 {
@@ -286,7 +895,39 @@ impl ASTVisitor<'_> for ASTCppTranspiler {
             self.visit_expression(argument);
         }
         self.add_text(")");
-        self.add_text(";");
+    }
+
+    /// Emits an anonymous function as a lambda. Its captures come from the
+    /// capture analysis: `mut` variables by reference, others by copy.
+    fn visit_closure_expression(&mut self, closure_expression: &ASTClosureExpression) {
+        self.add_text("[");
+        for (i, capture) in closure_expression.captures.borrow().iter().enumerate() {
+            if i != 0 {
+                self.add_text(",");
+                self.add_whitespace();
+            }
+            if capture.by_reference {
+                self.add_text("&");
+            }
+            self.add_variable(&capture.identifier);
+        }
+        self.add_text("](");
+        for (i, parameter) in closure_expression.parameters.iter().enumerate() {
+            if i != 0 {
+                self.add_text(",");
+                self.add_whitespace();
+            }
+            self.add_parameter(parameter);
+        }
+        self.add_text(")");
+        self.add_whitespace();
+        if let Some(type_annotation) = &closure_expression.type_annotation {
+            self.add_text("->");
+            self.add_whitespace();
+            self.add_value_type(&Some(*type_annotation.clone()));
+            self.add_whitespace();
+        }
+        self.visit_statement(&closure_expression.body);
     }
 
     fn visit_assignment_expression(&mut self, assignment_expression: &ASTAssignmentExpression) {
@@ -294,7 +935,7 @@ impl ASTVisitor<'_> for ASTCppTranspiler {
         self.add_whitespace();
         self.add_text("=");
         self.add_whitespace();
-        self.visit_expression(&assignment_expression.expression);
+        self.add_assigned_value(&assignment_expression.expression);
         self.add_text(";");
     }
 
@@ -306,7 +947,75 @@ impl ASTVisitor<'_> for ASTCppTranspiler {
         self.add_whitespace();
         self.add_text("=");
         self.add_whitespace();
-        self.visit_expression(&array_assignment_expression.expression);
+        self.add_assigned_value(&array_assignment_expression.expression);
+        self.add_text(";");
+    }
+
+    fn visit_struct_literal_expression(
+        &mut self,
+        struct_literal_expression: &ASTStructLiteralExpression,
+    ) {
+        let mut fields: Vec<&ASTStructLiteralField> =
+            struct_literal_expression.fields.iter().collect();
+        if let Some(order) = self
+            .structs
+            .get(&struct_literal_expression.identifier.lexeme)
+        {
+            fields.sort_by_key(|field| {
+                order
+                    .iter()
+                    .position(|name| name == &field.identifier.lexeme)
+                    .unwrap_or(order.len())
+            });
+        }
+
+        self.add_text(&struct_literal_expression.identifier.lexeme);
+        self.add_text("{");
+        for (i, field) in fields.iter().enumerate() {
+            if i != 0 {
+                self.add_text(",");
+            }
+            self.add_whitespace();
+            self.add_text(&format!(".{} =", field.identifier.lexeme));
+            self.add_whitespace();
+            self.visit_expression(&field.expression);
+        }
+        if !fields.is_empty() {
+            self.add_whitespace();
+        }
+        self.add_text("}");
+    }
+
+    fn visit_field_access_expression(
+        &mut self,
+        field_access_expression: &ASTFieldAccessExpression,
+    ) {
+        self.visit_expression(&field_access_expression.object);
+        self.add_text(".");
+        self.add_text(&field_access_expression.field.lexeme);
+    }
+
+    fn visit_field_assignment_expression(
+        &mut self,
+        field_assignment_expression: &ASTFieldAssignmentExpression,
+    ) {
+        self.visit_field_access_expression(&field_assignment_expression.field_access);
+        self.add_whitespace();
+        self.add_text("=");
+        self.add_whitespace();
+        self.add_assigned_value(&field_assignment_expression.expression);
+        self.add_text(";");
+    }
+
+    fn visit_compound_assignment_expression(
+        &mut self,
+        compound_assignment_expression: &ASTCompoundAssignmentExpression,
+    ) {
+        self.visit_expression(&compound_assignment_expression.target);
+        self.add_whitespace();
+        self.add_text(&compound_assignment_expression.operator.token.lexeme);
+        self.add_whitespace();
+        self.add_assigned_value(&compound_assignment_expression.expression);
         self.add_text(";");
     }
 
@@ -338,11 +1047,14 @@ impl ASTVisitor<'_> for ASTCppTranspiler {
     }
 
     fn visit_number_expression(&mut self, number: &ASTNumberExpression) {
-        self.result.push_str(&format!("{}", number.num.lexeme,));
+        self.add_number(number, false);
     }
 
     fn visit_string_expression(&mut self, string: &ASTStringExpression) {
-        self.result.push_str(&format!("{}", string.token.lexeme,));
+        let quote = if string.is_char() { '\'' } else { '"' };
+        self.result.push(quote);
+        self.result.push_str(&escape_cpp(&string.value, quote));
+        self.result.push(quote);
     }
 
     fn visit_boolean_expression(&mut self, boolean: &ASTBooleanExpression) {
@@ -350,18 +1062,29 @@ impl ASTVisitor<'_> for ASTCppTranspiler {
     }
 
     fn visit_unary_expression(&mut self, unary_expression: &ASTUnaryExpression) {
+        // `-int8_t{128}` narrows 128 into an `int8_t` before negating it, so
+        // the sign goes inside the braces.
+        if let (ASTUnaryOperatorKind::Subtraction, ASTExpressionKind::Number(number)) = (
+            &unary_expression.operator.kind,
+            &unary_expression.operand.kind,
+        ) {
+            self.add_number(number, true);
+            return;
+        }
         self.result
             .push_str(&format!("{}", unary_expression.operator.token.lexeme,));
-        self.visit_expression(&unary_expression.operand);
+        self.add_operand(&unary_expression.operand, UNARY_PRECEDENCE);
     }
 
     fn visit_binary_expression(&mut self, binary_expression: &ASTBinaryExpression) {
-        self.visit_expression(&binary_expression.left);
+        let precedence = binary_expression.operator.precedence();
+        // Left associative: `a - (b - c)` keeps its parentheses.
+        self.add_operand(&binary_expression.left, precedence);
         self.add_whitespace();
         self.result
             .push_str(&format!("{}", binary_expression.operator.token.lexeme,));
         self.add_whitespace();
-        self.visit_expression(&binary_expression.right);
+        self.add_operand(&binary_expression.right, precedence + 1);
     }
 
     fn visit_parenthesized_expression(
@@ -386,6 +1109,59 @@ impl ASTVisitor<'_> for ASTCppTranspiler {
             .map(|s| format!("#include <{}>\n", s))
             .collect();
 
+        if self.namespace.is_some() {
+            match self.output {
+                Output::Header(_) => self.add_text("}\n"),
+                _ => {
+                    self.add_whitespace();
+                    self.add_text("}");
+                }
+            }
+        }
+        let main = std::mem::take(&mut self.main);
+        self.add_text(&main);
+
         self.result.insert_str(0, &formatted_includes.join("\n"));
+        let module_includes: String = self
+            .module_includes
+            .iter()
+            .map(|header| format!("#include \"{}\"\n", header))
+            .collect();
+        self.result.insert_str(0, &module_includes);
+
+        match &self.output {
+            Output::Combined => {}
+            Output::Header(guard) => {
+                let guard = format!("#ifndef {}\n#define {}\n", guard, guard);
+                self.result.insert_str(0, &guard);
+                self.result.push_str("#endif\n");
+            }
+            Output::Source(header) => {
+                let include = format!("#include \"{}\"\n", header);
+                self.result.insert_str(0, &include);
+            }
+        }
+    }
+}
+
+/// Escapes a decoded literal value so it can be placed between `quote`s in
+/// C++ source. Other control characters use three digit octal escapes, which,
+/// unlike `\x`, cannot swallow a following digit.
+fn escape_cpp(value: &str, quote: char) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            '\r' => escaped.push_str("\\r"),
+            c if c == quote => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c if c.is_ascii_control() => escaped.push_str(&format!("\\{:03o}", c as u32)),
+            c => escaped.push(c),
+        }
     }
+    escaped
 }
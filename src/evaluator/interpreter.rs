@@ -1,30 +1,104 @@
 use crate::parser::visitor::ASTVisitor;
+use crate::evaluator::types::{Float, Int, Type};
 use crate::parser::*;
+use crate::tokenizer::NumberValue;
+use std::cell::RefCell;
 use std::collections::HashMap;
 use std::fmt;
+use std::rc::Rc;
+
+/// The variables of a function call. Each one lives in its own cell, which
+/// closures created in the call share.
+pub type Environment = HashMap<String, Rc<RefCell<VariableType>>>;
 
 pub struct ASTInterpreter {
     pub last_value: Option<VariableType>,
-    pub variables: HashMap<String, VariableType>,
+    pub variables: Environment,
+    pub structs: HashMap<String, Vec<String>>,
+    pub functions: HashMap<String, Rc<ASTFuncDeclStatement>>,
+    // Variables of the callers of the function being executed, outermost
+    // (the globals) first.
+    frames: Vec<Environment>,
+    // Set by `return` until the enclosing call has picked it up. While it is
+    // set no further statements are executed.
+    return_value: Option<VariableType>,
+    /// What the program returned, either from `main` or a top level `return`.
+    pub exit_value: Option<VariableType>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum VariableType {
     Number(i64),
+    /// An integer of a fixed width, from a literal with a suffix such as
+    /// `255u8`. Arithmetic on it wraps around at that width.
+    FixedInt(i128, Int),
+    Float(f64),
+    Bool(bool),
     String(String),
     Array(Vec<VariableType>),
+    Struct(String, Vec<(String, VariableType)>),
+    Function(Rc<Closure>),
+    Void,
+}
+
+/// A function used as a value: an anonymous function, or a declared one
+/// referred to by name.
+#[derive(Debug)]
+pub struct Closure {
+    parameters: Vec<FuncDeclParameter>,
+    body: ASTStatement,
+    // The variables in scope where the function was created. Their cells are
+    // shared with that scope, so an assignment on either side is seen by the
+    // other.
+    environment: Environment,
+}
+
+impl PartialEq for Closure {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
 }
 
 impl fmt::Display for VariableType {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             VariableType::Number(n) => write!(f, "{}", n),
+            VariableType::FixedInt(n, _) => write!(f, "{}", n),
+            VariableType::Float(n) => write!(f, "{}", n),
+            VariableType::Bool(b) => write!(f, "{}", b),
             VariableType::String(s) => write!(f, "{}", s),
             VariableType::Array(arr) => {
                 let formatted_elements: Vec<String> =
                     arr.iter().map(|elem| elem.to_string()).collect();
                 write!(f, "[{}]", formatted_elements.join(", "))
             }
+            VariableType::Struct(name, fields) => {
+                let formatted_fields: Vec<String> = fields
+                    .iter()
+                    .map(|(field, value)| format!("{}: {}", field, value))
+                    .collect();
+                write!(f, "{} {{ {} }}", name, formatted_fields.join(", "))
+            }
+            VariableType::Function(_) => write!(f, "fn"),
+            VariableType::Void => write!(f, ""),
+        }
+    }
+}
+
+impl VariableType {
+    /// The value of an integer, with or without a fixed width.
+    pub fn as_integer(&self) -> Option<i128> {
+        match self {
+            VariableType::Number(n) => Some(*n as i128),
+            VariableType::FixedInt(n, _) => Some(*n),
+            _ => None,
+        }
+    }
+
+    fn as_index(&self) -> usize {
+        match self.as_integer() {
+            Some(index) => index as usize,
+            None => panic!("Array index is not a number"),
         }
     }
 }
@@ -34,7 +108,177 @@ impl ASTInterpreter {
         Self {
             last_value: None,
             variables: HashMap::new(),
+            structs: HashMap::new(),
+            functions: HashMap::new(),
+            frames: Vec::new(),
+            return_value: None,
+            exit_value: None,
+        }
+    }
+
+    fn lookup_cell(&self, name: &str) -> Option<&Rc<RefCell<VariableType>>> {
+        self.variables
+            .get(name)
+            .or_else(|| self.frames.first().and_then(|globals| globals.get(name)))
+    }
+
+    fn lookup_variable(&self, name: &str) -> Option<VariableType> {
+        self.lookup_cell(name).map(|cell| cell.borrow().clone())
+    }
+
+    /// Declares a new variable, which hides any earlier one of the same name
+    /// without changing it for closures that captured it.
+    fn define_variable(&mut self, name: &str, value: VariableType) {
+        self.variables
+            .insert(name.to_string(), Rc::new(RefCell::new(value)));
+    }
+
+    /// Runs `body` in a scope of its own. The variables it declares are
+    /// dropped when it ends, uncovering outer ones of the same name, while
+    /// assignments to outer variables stay visible.
+    fn with_scope<T>(&mut self, body: impl FnOnce(&mut Self) -> T) -> T {
+        let outer = self.variables.clone();
+        let result = body(self);
+        self.variables = outer;
+        result
+    }
+
+    fn assign_variable(&mut self, name: &str, value: VariableType) {
+        match self.lookup_cell(name) {
+            Some(cell) => *cell.borrow_mut() = value,
+            None => self.define_variable(name, value),
+        }
+    }
+
+    /// Executes a single top level statement and returns the value it
+    /// produced, if any: the value of an expression statement or the value
+    /// passed to `return`.
+    pub fn execute(&mut self, statement: &ASTStatement) -> Option<VariableType> {
+        self.last_value = None;
+        self.visit_statement(statement);
+        if let Some(value) = self.return_value.take() {
+            return Some(value);
+        }
+        match statement.kind {
+            ASTStatementKind::Expression(_) => self.last_value.clone(),
+            _ => None,
+        }
+    }
+
+    /// Drops the call frames of an execution that was aborted by a runtime
+    /// error, restoring the global variables.
+    pub fn unwind(&mut self) {
+        if !self.frames.is_empty() {
+            self.variables = self.frames.swap_remove(0);
+            self.frames.clear();
+        }
+        self.return_value = None;
+    }
+
+    pub fn call_function(&mut self, name: &str, arguments: Vec<VariableType>) -> VariableType {
+        let function = match self.functions.get(name) {
+            Some(function) => function.clone(),
+            None => panic!("Undefined function: {}", name),
+        };
+        if function.parameters.len() != arguments.len() {
+            panic!(
+                "Function {} takes {} arguments but {} were supplied",
+                name,
+                function.parameters.len(),
+                arguments.len()
+            );
+        }
+        self.invoke(
+            &function.parameters,
+            &function.body,
+            Environment::new(),
+            arguments,
+        )
+    }
+
+    fn call_closure(&mut self, closure: &Closure, arguments: Vec<VariableType>) -> VariableType {
+        if closure.parameters.len() != arguments.len() {
+            panic!(
+                "Function takes {} arguments but {} were supplied",
+                closure.parameters.len(),
+                arguments.len()
+            );
+        }
+        self.invoke(
+            &closure.parameters,
+            &closure.body,
+            closure.environment.clone(),
+            arguments,
+        )
+    }
+
+    /// Runs `body` with the parameters bound to the arguments in a new frame
+    /// on top of the variables of `environment`.
+    fn invoke(
+        &mut self,
+        parameters: &[FuncDeclParameter],
+        body: &ASTStatement,
+        environment: Environment,
+        arguments: Vec<VariableType>,
+    ) -> VariableType {
+        let caller = std::mem::replace(&mut self.variables, environment);
+        self.frames.push(caller);
+        for (parameter, argument) in parameters.iter().zip(arguments) {
+            self.define_variable(&parameter.identifier.lexeme, argument);
+        }
+        self.visit_statement(body);
+        self.variables = self.frames.pop().unwrap();
+
+        let value = self.return_value.take().unwrap_or(VariableType::Void);
+        self.last_value = Some(value.clone());
+        value
+    }
+
+    fn evaluate_call_expression(&mut self, call_expr: &ASTCallExpression) -> VariableType {
+        let arguments = call_expr
+            .arguments
+            .iter()
+            .map(|argument| self.evaluate_expression(argument))
+            .collect();
+        if call_expr.qualifier.is_empty() {
+            if let Some(VariableType::Function(closure)) =
+                self.lookup_variable(&call_expr.identifier.lexeme)
+            {
+                return self.call_closure(&closure, arguments);
+            }
+        }
+        self.call_function(&call_expr.identifier.lexeme, arguments)
+    }
+
+    fn evaluate_closure_expression(&mut self, closure_expr: &ASTClosureExpression) -> VariableType {
+        VariableType::Function(Rc::new(Closure {
+            parameters: closure_expr.parameters.clone(),
+            body: (*closure_expr.body).clone(),
+            environment: self.variables.clone(),
+        }))
+    }
+
+    /// Formats the arguments of `std::println` like C++ `std::format` does
+    /// when the first one is a format string, otherwise joins them.
+    fn format_arguments(arguments: &[VariableType]) -> String {
+        if let Some(VariableType::String(format)) = arguments.first() {
+            if format.contains("{}") {
+                let mut result = String::new();
+                let mut rest = arguments[1..].iter();
+                let mut parts = format.split("{}").peekable();
+                while let Some(part) = parts.next() {
+                    result.push_str(part);
+                    if parts.peek().is_some() {
+                        if let Some(argument) = rest.next() {
+                            result.push_str(&argument.to_string());
+                        }
+                    }
+                }
+                return result;
+            }
         }
+        let arguments: Vec<String> = arguments.iter().map(|arg| arg.to_string()).collect();
+        arguments.join(" ")
     }
 
     fn evaluate_expression(&mut self, expression: &ASTExpression) -> VariableType {
@@ -42,27 +286,186 @@ impl ASTInterpreter {
         self.last_value.clone().unwrap()
     }
 
+    /// Applies a binary operator where at least one side is a float. The
+    /// other side is converted, as C++ does in mixed arithmetic.
+    fn float_binary(
+        operator: &ASTBinaryOperatorKind,
+        left: VariableType,
+        right: VariableType,
+    ) -> VariableType {
+        let as_float = |value: VariableType| match value {
+            VariableType::Number(n) => n as f64,
+            VariableType::FixedInt(n, _) => n as f64,
+            VariableType::Float(n) => n,
+            _ => panic!("Binary operator applied to non-number"),
+        };
+        let (left, right) = (as_float(left), as_float(right));
+        match operator {
+            ASTBinaryOperatorKind::Addition => VariableType::Float(left + right),
+            ASTBinaryOperatorKind::Subtraction => VariableType::Float(left - right),
+            ASTBinaryOperatorKind::Star => VariableType::Float(left * right),
+            ASTBinaryOperatorKind::Division => VariableType::Float(left / right),
+            ASTBinaryOperatorKind::Modulo => VariableType::Float(left % right),
+            ASTBinaryOperatorKind::Equals => VariableType::Bool(left == right),
+            ASTBinaryOperatorKind::Inequal => VariableType::Bool(left != right),
+            ASTBinaryOperatorKind::LowerThan => VariableType::Bool(left < right),
+            ASTBinaryOperatorKind::GreaterThan => VariableType::Bool(left > right),
+            ASTBinaryOperatorKind::LowerEquals => VariableType::Bool(left <= right),
+            ASTBinaryOperatorKind::GreaterEquals => VariableType::Bool(left >= right),
+            ASTBinaryOperatorKind::BwAnd
+            | ASTBinaryOperatorKind::BwOr
+            | ASTBinaryOperatorKind::BwXor
+            | ASTBinaryOperatorKind::BwShl
+            | ASTBinaryOperatorKind::BwShr => panic!("Bitwise operator applied to a float"),
+            ASTBinaryOperatorKind::And | ASTBinaryOperatorKind::Or => {
+                panic!("Logical operator applied to a float")
+            }
+        }
+    }
+
+    /// Brings `value` into the range of `int`, dropping the bits that do not
+    /// fit as the C++ type does.
+    fn wrap(value: i128, int: Int) -> i128 {
+        let bits = int.bits();
+        let value = value & ((1i128 << bits) - 1);
+        if int.is_signed() && value >= 1i128 << (bits - 1) {
+            value - (1i128 << bits)
+        } else {
+            value
+        }
+    }
+
+    /// The width the operands of a binary operator are computed in when at
+    /// least one has a fixed width: the wider one, as in C++. Plain integers
+    /// take on the width of the other side.
+    fn fixed_width(left: &VariableType, right: &VariableType) -> Option<Int> {
+        match (left, right) {
+            (VariableType::FixedInt(_, left), VariableType::FixedInt(_, right)) => {
+                Some(if right.bits() > left.bits() {
+                    *right
+                } else {
+                    *left
+                })
+            }
+            (VariableType::FixedInt(_, int), _) | (_, VariableType::FixedInt(_, int)) => Some(*int),
+            _ => None,
+        }
+    }
+
+    fn fixed_binary(
+        operator: &ASTBinaryOperatorKind,
+        left: i128,
+        right: i128,
+        int: Int,
+    ) -> VariableType {
+        let value = match operator {
+            ASTBinaryOperatorKind::Addition => left.wrapping_add(right),
+            ASTBinaryOperatorKind::Subtraction => left.wrapping_sub(right),
+            ASTBinaryOperatorKind::Star => left.wrapping_mul(right),
+            ASTBinaryOperatorKind::Division => left / right,
+            ASTBinaryOperatorKind::Modulo => left % right,
+            ASTBinaryOperatorKind::BwAnd => left & right,
+            ASTBinaryOperatorKind::BwOr => left | right,
+            ASTBinaryOperatorKind::BwXor => left ^ right,
+            ASTBinaryOperatorKind::BwShl => left.wrapping_shl(right as u32),
+            ASTBinaryOperatorKind::BwShr => left.wrapping_shr(right as u32),
+            ASTBinaryOperatorKind::Equals => return VariableType::Bool(left == right),
+            ASTBinaryOperatorKind::Inequal => return VariableType::Bool(left != right),
+            ASTBinaryOperatorKind::LowerThan => return VariableType::Bool(left < right),
+            ASTBinaryOperatorKind::GreaterThan => return VariableType::Bool(left > right),
+            ASTBinaryOperatorKind::LowerEquals => return VariableType::Bool(left <= right),
+            ASTBinaryOperatorKind::GreaterEquals => return VariableType::Bool(left >= right),
+            ASTBinaryOperatorKind::And | ASTBinaryOperatorKind::Or => unreachable!(),
+        };
+        VariableType::FixedInt(Self::wrap(value, int), int)
+    }
+
+    /// Applies a binary operator other than the short-circuiting `&&` and
+    /// `||`.
+    fn binary(
+        operator: &ASTBinaryOperatorKind,
+        left: VariableType,
+        right: VariableType,
+    ) -> VariableType {
+        if let (Some(int), Some(left), Some(right)) = (
+            Self::fixed_width(&left, &right),
+            left.as_integer(),
+            right.as_integer(),
+        ) {
+            return Self::fixed_binary(operator, left, right, int);
+        }
+        let (left, right) = match (left, right) {
+            (VariableType::Number(left), VariableType::Number(right)) => (left, right),
+            (VariableType::Bool(left), VariableType::Bool(right)) => {
+                return VariableType::Bool(match operator {
+                    ASTBinaryOperatorKind::Equals => left == right,
+                    ASTBinaryOperatorKind::Inequal => left != right,
+                    _ => panic!("Binary operator applied to bools"),
+                });
+            }
+            (VariableType::String(left), VariableType::String(right)) => {
+                // Chars are strings of one character here, and compare the
+                // same way.
+                return match operator {
+                    ASTBinaryOperatorKind::Addition => VariableType::String(left + &right),
+                    ASTBinaryOperatorKind::Equals => VariableType::Bool(left == right),
+                    ASTBinaryOperatorKind::Inequal => VariableType::Bool(left != right),
+                    ASTBinaryOperatorKind::LowerThan => VariableType::Bool(left < right),
+                    ASTBinaryOperatorKind::GreaterThan => VariableType::Bool(left > right),
+                    ASTBinaryOperatorKind::LowerEquals => VariableType::Bool(left <= right),
+                    ASTBinaryOperatorKind::GreaterEquals => VariableType::Bool(left >= right),
+                    _ => panic!("Binary operator applied to strings"),
+                };
+            }
+            (left, right) => return Self::float_binary(operator, left, right),
+        };
+        match operator {
+            ASTBinaryOperatorKind::Addition => VariableType::Number(left + right),
+            ASTBinaryOperatorKind::Subtraction => VariableType::Number(left - right),
+            ASTBinaryOperatorKind::Star => VariableType::Number(left * right),
+            ASTBinaryOperatorKind::Division => VariableType::Number(left / right),
+            ASTBinaryOperatorKind::Modulo => VariableType::Number(left % right),
+            ASTBinaryOperatorKind::BwAnd => VariableType::Number(left & right),
+            ASTBinaryOperatorKind::BwOr => VariableType::Number(left | right),
+            ASTBinaryOperatorKind::BwXor => VariableType::Number(left ^ right),
+            ASTBinaryOperatorKind::BwShl => VariableType::Number(left << right),
+            ASTBinaryOperatorKind::BwShr => VariableType::Number(left >> right),
+            ASTBinaryOperatorKind::Equals => VariableType::Bool(left == right),
+            ASTBinaryOperatorKind::Inequal => VariableType::Bool(left != right),
+            ASTBinaryOperatorKind::LowerThan => VariableType::Bool(left < right),
+            ASTBinaryOperatorKind::GreaterThan => VariableType::Bool(left > right),
+            ASTBinaryOperatorKind::LowerEquals => VariableType::Bool(left <= right),
+            ASTBinaryOperatorKind::GreaterEquals => VariableType::Bool(left >= right),
+            ASTBinaryOperatorKind::And | ASTBinaryOperatorKind::Or => unreachable!(),
+        }
+    }
+
+    /// The value of a condition or an operand of `&&`, `||` and `!`.
+    fn is_true(value: &VariableType) -> bool {
+        match value {
+            VariableType::Bool(b) => *b,
+            _ => panic!("Condition is not a bool"),
+        }
+    }
+
     fn evaluate_assignment_expression(
         &mut self,
         assignment_expr: &ASTAssignmentExpression,
     ) -> VariableType {
         let value = self.evaluate_expression(&assignment_expr.expression);
-        self.variables
-            .insert(assignment_expr.identifier.lexeme.clone(), value.clone());
+        self.assign_variable(&assignment_expr.identifier.lexeme, value.clone());
         value
     }
 
     fn evaluate_range_expression(&mut self, range_expr: &ASTRangeExpression) -> VariableType {
-        let start = if let VariableType::Number(n) = self.evaluate_expression(&range_expr.start) {
-            n
-        } else {
-            panic!("Range start is not a number")
+        let start = match self.evaluate_expression(&range_expr.start).as_integer() {
+            Some(n) => n as i64,
+            None => panic!("Range start is not a number"),
         };
 
-        let end = if let VariableType::Number(n) = self.evaluate_expression(&range_expr.end) {
-            n
-        } else {
-            panic!("Range end is not a number")
+        let end = match self.evaluate_expression(&range_expr.end).as_integer() {
+            Some(n) => n as i64,
+            None => panic!("Range end is not a number"),
         };
 
         let range: Vec<VariableType> = (start..end).map(VariableType::Number).collect();
@@ -83,11 +486,8 @@ impl ASTInterpreter {
         array_index_expr: &ASTArrayIndexExpression,
     ) -> VariableType {
         if let VariableType::Array(array) = self.evaluate_expression(&array_index_expr.array) {
-            if let VariableType::Number(index) = self.evaluate_expression(&array_index_expr.index) {
-                array[index as usize].clone()
-            } else {
-                panic!("Array index is not a number")
-            }
+            let index = self.evaluate_expression(&array_index_expr.index).as_index();
+            array[index].clone()
         } else {
             panic!("Array index applied to non-array")
         }
@@ -103,20 +503,12 @@ impl ASTInterpreter {
             let array_var = &array_index_expr.array;
             if let ASTExpressionKind::Variable(variable_expr) = &array_var.kind {
                 let array_name = &variable_expr.identifier.lexeme;
-                if let Some(VariableType::Array(mut array)) =
-                    self.variables.get(array_name).cloned()
-                {
-                    if let VariableType::Number(index) =
-                        self.evaluate_expression(&array_index_expr.index)
-                    {
-                        let value = self.evaluate_expression(&array_assignment_expr.expression);
-                        array[index as usize] = value.clone();
-                        self.variables
-                            .insert(array_name.clone(), VariableType::Array(array));
-                        return value;
-                    } else {
-                        panic!("Array index is not a number")
-                    }
+                if let Some(VariableType::Array(mut array)) = self.lookup_variable(array_name) {
+                    let index = self.evaluate_expression(&array_index_expr.index).as_index();
+                    let value = self.evaluate_expression(&array_assignment_expr.expression);
+                    array[index] = value.clone();
+                    self.assign_variable(array_name, VariableType::Array(array));
+                    return value;
                 } else {
                     panic!("Array assignment applied to non-array")
                 }
@@ -127,10 +519,171 @@ impl ASTInterpreter {
             panic!("Expected array index expression for array assignment")
         }
     }
+
+    fn evaluate_struct_literal_expression(
+        &mut self,
+        struct_literal_expr: &ASTStructLiteralExpression,
+    ) -> VariableType {
+        let name = &struct_literal_expr.identifier.lexeme;
+        let field_names = match self.structs.get(name) {
+            Some(field_names) => field_names.clone(),
+            None => panic!("Undefined struct: {}", name),
+        };
+
+        for field in &struct_literal_expr.fields {
+            if !field_names.contains(&field.identifier.lexeme) {
+                panic!("Struct {} has no field {}", name, field.identifier.lexeme);
+            }
+        }
+
+        let fields = field_names
+            .iter()
+            .map(|field_name| {
+                match struct_literal_expr
+                    .fields
+                    .iter()
+                    .find(|field| &field.identifier.lexeme == field_name)
+                {
+                    Some(field) => (
+                        field_name.clone(),
+                        self.evaluate_expression(&field.expression),
+                    ),
+                    None => panic!("Missing field {} in struct {}", field_name, name),
+                }
+            })
+            .collect();
+        VariableType::Struct(name.clone(), fields)
+    }
+
+    fn evaluate_field_access_expression(
+        &mut self,
+        field_access_expr: &ASTFieldAccessExpression,
+    ) -> VariableType {
+        if let VariableType::Struct(name, fields) =
+            self.evaluate_expression(&field_access_expr.object)
+        {
+            match fields
+                .into_iter()
+                .find(|(field, _)| field == &field_access_expr.field.lexeme)
+            {
+                Some((_, value)) => value,
+                None => panic!(
+                    "Struct {} has no field {}",
+                    name, field_access_expr.field.lexeme
+                ),
+            }
+        } else {
+            panic!("Field access applied to non-struct")
+        }
+    }
+
+    fn evaluate_field_assignment_expression(
+        &mut self,
+        field_assignment_expr: &ASTFieldAssignmentExpression,
+    ) -> VariableType {
+        let value = self.evaluate_expression(&field_assignment_expr.expression);
+        self.assign_field_place(&field_assignment_expr.field_access, value.clone());
+        value
+    }
+
+    fn evaluate_compound_assignment_expression(
+        &mut self,
+        compound_assignment_expr: &ASTCompoundAssignmentExpression,
+    ) -> VariableType {
+        let current = self.evaluate_expression(&compound_assignment_expr.target);
+        let operand = self.evaluate_expression(&compound_assignment_expr.expression);
+        let value = Self::binary(&compound_assignment_expr.operator.kind, current, operand);
+        self.assign_place(&compound_assignment_expr.target, value.clone());
+        value
+    }
+
+    /// Stores `value` in the variable, array element or field `target`
+    /// denotes.
+    fn assign_place(&mut self, target: &ASTExpression, value: VariableType) {
+        match &target.kind {
+            ASTExpressionKind::Variable(variable_expr) => {
+                self.assign_variable(&variable_expr.identifier.lexeme, value)
+            }
+            ASTExpressionKind::ArrayIndex(array_index_expr) => {
+                let index = self.evaluate_expression(&array_index_expr.index).as_index();
+                let mut array = self.evaluate_expression(&array_index_expr.array);
+                match &mut array {
+                    VariableType::Array(elements) => elements[index] = value,
+                    _ => panic!("Array assignment applied to non-array"),
+                }
+                self.assign_place(&array_index_expr.array, array);
+            }
+            ASTExpressionKind::FieldAccess(field_access_expr) => {
+                self.assign_field_place(field_access_expr, value)
+            }
+            _ => panic!("Cannot assign to {:?}", target.kind),
+        }
+    }
+
+    /// Stores `value` in the field `field_access_expr` denotes, whose object
+    /// may itself be an array element or a field.
+    fn assign_field_place(
+        &mut self,
+        field_access_expr: &ASTFieldAccessExpression,
+        value: VariableType,
+    ) {
+        let mut object = self.evaluate_expression(&field_access_expr.object);
+        Self::assign_field(
+            &mut object,
+            std::slice::from_ref(&field_access_expr.field.lexeme),
+            value,
+        );
+        self.assign_place(&field_access_expr.object, object);
+    }
+
+    fn assign_field(target: &mut VariableType, path: &[String], value: VariableType) {
+        if let VariableType::Struct(name, fields) = target {
+            match fields.iter_mut().find(|(field, _)| field == &path[0]) {
+                Some((_, field_value)) => {
+                    if path.len() == 1 {
+                        *field_value = value;
+                    } else {
+                        Self::assign_field(field_value, &path[1..], value);
+                    }
+                }
+                None => panic!("Struct {} has no field {}", name, path[0]),
+            }
+        } else {
+            panic!("Field assignment applied to non-struct")
+        }
+    }
+
+    fn matches_pattern(&mut self, pattern: &ASTMatchPattern, value: &VariableType) -> bool {
+        match pattern {
+            ASTMatchPattern::Literal(literal) => {
+                let literal = self.evaluate_expression(literal);
+                match (literal.as_integer(), value.as_integer()) {
+                    (Some(literal), Some(value)) => literal == value,
+                    _ => literal == *value,
+                }
+            }
+            ASTMatchPattern::Range(range) => {
+                let start = self.evaluate_expression(&range.start);
+                let end = self.evaluate_expression(&range.end);
+                match (start.as_integer(), end.as_integer(), value.as_integer()) {
+                    (Some(start), Some(end), Some(value)) => (start..end).contains(&value),
+                    _ => false,
+                }
+            }
+            ASTMatchPattern::Binding(identifier) => {
+                self.define_variable(&identifier.lexeme, value.clone());
+                true
+            }
+            ASTMatchPattern::Wildcard(_) => true,
+        }
+    }
 }
 
 impl ASTVisitor<'_> for ASTInterpreter {
     fn visit_statement(&mut self, statement: &ASTStatement) {
+        if self.return_value.is_some() {
+            return;
+        }
         match &statement.kind {
             ASTStatementKind::Let(let_statement) => self.visit_let_statement(let_statement),
             ASTStatementKind::If(if_statement) => self.visit_if_statement(if_statement),
@@ -142,8 +695,15 @@ impl ASTVisitor<'_> for ASTInterpreter {
                 self.visit_return_statement(return_statement)
             }
             ASTStatementKind::For(for_statement) => self.visit_for_statement(for_statement),
+            ASTStatementKind::While(while_statement) => self.visit_while_statement(while_statement),
+            ASTStatementKind::Match(match_statement) => self.visit_match_statement(match_statement),
+            ASTStatementKind::Struct(struct_statement) => {
+                self.visit_struct_statement(struct_statement)
+            }
+            ASTStatementKind::Namespace(namespace_statement) => {
+                self.visit_namespace_statement(namespace_statement)
+            }
             ASTStatementKind::Expression(expr_statement) => self.visit_expression(expr_statement),
-            _ => unimplemented!("Unsupported statement type {:?}", &statement.kind),
         }
     }
 
@@ -154,6 +714,7 @@ impl ASTVisitor<'_> for ASTInterpreter {
             }
             ASTExpressionKind::Number(number_expr) => self.visit_number_expression(number_expr),
             ASTExpressionKind::String(string_expr) => self.visit_string_expression(string_expr),
+            ASTExpressionKind::Boolean(boolean_expr) => self.visit_boolean_expression(boolean_expr),
             ASTExpressionKind::Unary(unary_expr) => self.visit_unary_expression(unary_expr),
             ASTExpressionKind::Binary(binary_expr) => self.visit_binary_expression(binary_expr),
             ASTExpressionKind::Parenthesized(paren_expr) => {
@@ -162,6 +723,7 @@ impl ASTVisitor<'_> for ASTInterpreter {
             ASTExpressionKind::StdCall(std_call_expr) => {
                 self.visit_std_call_expression(std_call_expr)
             }
+            ASTExpressionKind::Call(call_expr) => self.visit_call_expression(call_expr),
             ASTExpressionKind::Assignment(assignment_expr) => {
                 self.visit_assignment_expression(assignment_expr)
             }
@@ -173,10 +735,28 @@ impl ASTVisitor<'_> for ASTInterpreter {
             ASTExpressionKind::ArrayAssignment(array_assignment_expr) => {
                 self.visit_array_assignment_expression(array_assignment_expr)
             }
+            ASTExpressionKind::StructLiteral(struct_literal_expr) => {
+                self.visit_struct_literal_expression(struct_literal_expr)
+            }
+            ASTExpressionKind::FieldAccess(field_access_expr) => {
+                self.visit_field_access_expression(field_access_expr)
+            }
+            ASTExpressionKind::FieldAssignment(field_assignment_expr) => {
+                self.visit_field_assignment_expression(field_assignment_expr)
+            }
+            ASTExpressionKind::CompoundAssignment(compound_assignment_expr) => {
+                self.visit_compound_assignment_expression(compound_assignment_expr)
+            }
+            ASTExpressionKind::Closure(closure_expr) => self.visit_closure_expression(closure_expr),
             _ => unimplemented!("Unsupported expression type {:?}", &expression.kind),
         }
     }
 
+    fn visit_call_expression(&mut self, call_expr: &ASTCallExpression) {
+        let value = self.evaluate_call_expression(call_expr);
+        self.last_value = Some(value);
+    }
+
     fn visit_assignment_expression(&mut self, assignment_expr: &ASTAssignmentExpression) {
         let value = self.evaluate_assignment_expression(assignment_expr);
         self.last_value = Some(value);
@@ -204,19 +784,70 @@ impl ASTVisitor<'_> for ASTInterpreter {
         let value = self.evaluate_array_assignment_expression(array_assignment_expr);
         self.last_value = Some(value);
     }
+    fn visit_struct_literal_expression(
+        &mut self,
+        struct_literal_expr: &ASTStructLiteralExpression,
+    ) {
+        let value = self.evaluate_struct_literal_expression(struct_literal_expr);
+        self.last_value = Some(value);
+    }
+
+    fn visit_field_access_expression(&mut self, field_access_expr: &ASTFieldAccessExpression) {
+        let value = self.evaluate_field_access_expression(field_access_expr);
+        self.last_value = Some(value);
+    }
+
+    fn visit_field_assignment_expression(
+        &mut self,
+        field_assignment_expr: &ASTFieldAssignmentExpression,
+    ) {
+        let value = self.evaluate_field_assignment_expression(field_assignment_expr);
+        self.last_value = Some(value);
+    }
+
+    fn visit_compound_assignment_expression(
+        &mut self,
+        compound_assignment_expr: &ASTCompoundAssignmentExpression,
+    ) {
+        let value = self.evaluate_compound_assignment_expression(compound_assignment_expr);
+        self.last_value = Some(value);
+    }
+
+    fn visit_closure_expression(&mut self, closure_expr: &ASTClosureExpression) {
+        let value = self.evaluate_closure_expression(closure_expr);
+        self.last_value = Some(value);
+    }
+
+    fn visit_struct_statement(&mut self, struct_statement: &ASTStructStatement) {
+        self.structs.insert(
+            struct_statement.identifier.lexeme.clone(),
+            struct_statement
+                .fields
+                .iter()
+                .map(|field| field.identifier.lexeme.clone())
+                .collect(),
+        );
+    }
+
     fn visit_boolean_expression(&mut self, boolean: &ASTBooleanExpression) {
-        // Implementation for visiting boolean expressions
+        self.last_value = Some(VariableType::Bool(boolean.value));
     }
 
     fn visit_let_statement(&mut self, let_statement: &ASTLetStatement) {
         let value = self.evaluate_expression(&let_statement.initializer);
-        self.variables
-            .insert(let_statement.identifier.lexeme.clone(), value);
+        self.define_variable(&let_statement.identifier.lexeme, value);
     }
 
     fn visit_variable_expression(&mut self, variable_expression: &ASTVariableExpression) {
-        if let Some(value) = self.variables.get(&variable_expression.identifier.lexeme) {
-            self.last_value = Some(value.clone());
+        let name = &variable_expression.identifier.lexeme;
+        if let Some(value) = self.lookup_variable(name) {
+            self.last_value = Some(value);
+        } else if let Some(function) = self.functions.get(name) {
+            self.last_value = Some(VariableType::Function(Rc::new(Closure {
+                parameters: function.parameters.clone(),
+                body: (*function.body).clone(),
+                environment: Environment::new(),
+            })));
         } else {
             panic!(
                 "Undefined variable: {}",
@@ -226,53 +857,60 @@ impl ASTVisitor<'_> for ASTInterpreter {
     }
 
     fn visit_number_expression(&mut self, number_expr: &ASTNumberExpression) {
-        match number_expr.num.lexeme.parse::<i64>() {
-            Ok(n) => self.last_value = Some(VariableType::Number(n)),
-            Err(e) => println!("Error: {}", e),
-        }
+        self.last_value = Some(match (number_expr.value, &number_expr.suffix) {
+            (NumberValue::Int(n), Some(Type::Int(int))) => {
+                VariableType::FixedInt(Self::wrap(n as i128, *int), *int)
+            }
+            (NumberValue::Int(n), _) => VariableType::Number(n as i64),
+            (NumberValue::Float(n), Some(Type::Float(Float::F32))) => {
+                VariableType::Float(n as f32 as f64)
+            }
+            (NumberValue::Float(n), _) => VariableType::Float(n),
+        });
     }
 
     fn visit_string_expression(&mut self, string_expr: &ASTStringExpression) {
-        self.last_value = Some(VariableType::String(string_expr.token.lexeme.clone()));
+        self.last_value = Some(VariableType::String(string_expr.value.clone()));
     }
 
     fn visit_unary_expression(&mut self, unary_expr: &ASTUnaryExpression) {
-        self.visit_expression(&unary_expr.operand);
-        let operand = if let VariableType::Number(n) = self.last_value.as_ref().unwrap() {
-            n
-        } else {
-            panic!("Unary operator applied to non-number")
-        };
-        self.last_value = Some(VariableType::Number(match unary_expr.operator.kind {
-            ASTUnaryOperatorKind::Subtraction => -operand,
-            ASTUnaryOperatorKind::BwNot => !operand,
-        }));
+        let operand = self.evaluate_expression(&unary_expr.operand);
+        self.last_value = Some(match (operand, &unary_expr.operator.kind) {
+            (VariableType::Number(n), ASTUnaryOperatorKind::Subtraction) => {
+                VariableType::Number(-n)
+            }
+            (VariableType::Number(n), ASTUnaryOperatorKind::BwNot) => VariableType::Number(!n),
+            (VariableType::FixedInt(n, int), ASTUnaryOperatorKind::Subtraction) => {
+                VariableType::FixedInt(Self::wrap(n.wrapping_neg(), int), int)
+            }
+            (VariableType::FixedInt(n, int), ASTUnaryOperatorKind::BwNot) => {
+                VariableType::FixedInt(Self::wrap(!n, int), int)
+            }
+            (VariableType::Bool(b), ASTUnaryOperatorKind::Not) => VariableType::Bool(!b),
+            (VariableType::Float(n), ASTUnaryOperatorKind::Subtraction) => VariableType::Float(-n),
+            _ => panic!("Unary operator applied to non-number"),
+        });
     }
 
     fn visit_binary_expression(&mut self, binary_expr: &ASTBinaryExpression) {
-        let left = if let VariableType::Number(n) = self.evaluate_expression(&binary_expr.left) {
-            n
-        } else {
-            panic!("Binary operator applied to non-number on left side")
-        };
-        let right = if let VariableType::Number(n) = self.evaluate_expression(&binary_expr.right) {
-            n
-        } else {
-            panic!("Binary operator applied to non-number on right side")
-        };
-        self.last_value = Some(VariableType::Number(match binary_expr.operator.kind {
-            ASTBinaryOperatorKind::Addition => left + right,
-            ASTBinaryOperatorKind::Subtraction => left - right,
-            ASTBinaryOperatorKind::Star => left * right,
-            ASTBinaryOperatorKind::Division => left / right,
-            ASTBinaryOperatorKind::BwAnd => left & right,
-            ASTBinaryOperatorKind::BwOr => left | right,
-            ASTBinaryOperatorKind::BwXor => left ^ right,
-            ASTBinaryOperatorKind::Equals => (left == right) as i64,
-            ASTBinaryOperatorKind::Inequal => (left != right) as i64,
-            ASTBinaryOperatorKind::LowerThan => (left < right) as i64,
-            ASTBinaryOperatorKind::GreaterThan => (left > right) as i64,
-        }));
+        let left = self.evaluate_expression(&binary_expr.left);
+        // The right side of `&&` and `||` is only evaluated when it decides
+        // the result.
+        match binary_expr.operator.kind {
+            ASTBinaryOperatorKind::And | ASTBinaryOperatorKind::Or => {
+                let left = Self::is_true(&left);
+                let result = if left == (binary_expr.operator.kind == ASTBinaryOperatorKind::Or) {
+                    left
+                } else {
+                    Self::is_true(&self.evaluate_expression(&binary_expr.right))
+                };
+                self.last_value = Some(VariableType::Bool(result));
+                return;
+            }
+            _ => {}
+        }
+        let right = self.evaluate_expression(&binary_expr.right);
+        self.last_value = Some(Self::binary(&binary_expr.operator.kind, left, right));
     }
 
     fn visit_parenthesized_expression(&mut self, paren_expr: &ASTParenthesizedExpression) {
@@ -280,48 +918,66 @@ impl ASTVisitor<'_> for ASTInterpreter {
     }
 
     fn visit_if_statement(&mut self, if_statement: &ASTIfStatement) {
-        let condition =
-            if let VariableType::Number(n) = self.evaluate_expression(&if_statement.condition) {
-                n
-            } else {
-                panic!("If condition is not a number")
-            };
-        if condition != 0 {
+        let condition = self.evaluate_expression(&if_statement.condition);
+        if Self::is_true(&condition) {
             self.visit_statement(&if_statement.then_branch);
         } else if let Some(else_branch) = &if_statement.else_branch {
             self.visit_statement(&else_branch.else_statement);
         }
     }
 
+    fn visit_match_statement(&mut self, match_statement: &ASTMatchStatement) {
+        let value = self.evaluate_expression(&match_statement.scrutinee);
+        for arm in &match_statement.arms {
+            // A binding pattern declares its variable for the arm only.
+            let matched = self.with_scope(|interpreter| {
+                let matched = interpreter.matches_pattern(&arm.pattern, &value);
+                if matched {
+                    interpreter.visit_statement(&arm.body);
+                }
+                matched
+            });
+            if matched {
+                return;
+            }
+        }
+        if let Some(else_branch) = &match_statement.else_branch {
+            self.visit_statement(&else_branch.else_statement);
+        }
+    }
+
     fn visit_block_statement(&mut self, block_statement: &ASTBlockStatement) {
-        for statement in &block_statement.statements {
-            self.visit_statement(statement);
+        self.with_scope(|interpreter| {
+            for statement in &block_statement.statements {
+                interpreter.visit_statement(statement);
+            }
+        });
+    }
+
+    fn visit_while_statement(&mut self, while_statement: &ASTWhileStatement) {
+        while self.return_value.is_none() {
+            let condition = self.evaluate_expression(&while_statement.condition);
+            if !Self::is_true(&condition) {
+                break;
+            }
+            self.visit_statement(&while_statement.body);
         }
     }
 
     fn visit_func_decl_statement(&mut self, func_decl_statement: &ASTFuncDeclStatement) {
-        // For now, let's just print the function declaration
-        println!("Function declaration:");
-        println!("Identifier: {:?}", func_decl_statement.identifier);
-        println!("Parameters: {:?}", func_decl_statement.parameters);
-        if let Some(type_annotation) = &func_decl_statement.type_annotation {
-            println!("Return Type: {:?}", type_annotation);
-        } else {
-            println!("Return Type: None");
-        }
-        println!("Body:");
-        self.visit_statement(&func_decl_statement.body);
+        self.functions.insert(
+            func_decl_statement.identifier.lexeme.clone(),
+            Rc::new(func_decl_statement.clone()),
+        );
     }
 
     fn visit_return_statement(&mut self, return_statement: &ASTReturnStatement) {
-        // For now, let's just print the return statement
-        println!("Return statement:");
-        if let Some(expr) = &return_statement.return_value {
-            println!("Expression:");
-            self.visit_expression(expr);
-        } else {
-            println!("No return value");
-        }
+        let value = match &return_statement.return_value {
+            Some(expr) => self.evaluate_expression(expr),
+            None => VariableType::Void,
+        };
+        self.last_value = Some(value.clone());
+        self.return_value = Some(value);
     }
 
     fn visit_for_statement(&mut self, for_statement: &ASTForStatement) {
@@ -329,9 +985,13 @@ impl ASTVisitor<'_> for ASTInterpreter {
 
         if let VariableType::Array(elements) = iterable {
             for element in elements {
-                self.variables
-                    .insert(for_statement.identifier.lexeme.clone(), element);
-                self.visit_statement(&for_statement.body);
+                if self.return_value.is_some() {
+                    break;
+                }
+                self.with_scope(|interpreter| {
+                    interpreter.define_variable(&for_statement.identifier.lexeme, element);
+                    interpreter.visit_statement(&for_statement.body);
+                });
             }
         } else {
             panic!("For statement iterable is not an array");
@@ -339,15 +999,13 @@ impl ASTVisitor<'_> for ASTInterpreter {
     }
     fn visit_std_call_expression(&mut self, std_call_expr: &ASTStdCallExpression) {
         if std_call_expr.identifier.lexeme == "println" {
-            let args: Vec<String> = std_call_expr
+            let args: Vec<VariableType> = std_call_expr
                 .arguments
                 .iter()
-                .map(|arg| {
-                    self.evaluate_expression(arg);
-                    self.last_value.as_ref().unwrap().to_string()
-                })
+                .map(|arg| self.evaluate_expression(arg))
                 .collect();
-            println!("{}", args.join(" "));
+            println!("{}", Self::format_arguments(&args));
+            self.last_value = Some(VariableType::Void);
         } else {
             unimplemented!("Unsupported std call type");
         }
@@ -358,5 +1016,26 @@ impl ASTVisitor<'_> for ASTInterpreter {
     ) {
     }
 
-    fn finalize(&mut self) {}
+    /// Runs `main` once all top level statements are executed, unless the
+    /// program already returned at the top level. `main` gets no `args` and
+    /// returns 0 when it declares a return type but ends without a `return`.
+    fn finalize(&mut self) {
+        self.exit_value = self.return_value.take();
+        if self.exit_value.is_some() {
+            return;
+        }
+        if let Some(main) = self.functions.get("main").cloned() {
+            let arguments = main
+                .parameters
+                .iter()
+                .map(|_| VariableType::Array(Vec::new()))
+                .collect();
+            let value = match self.call_function("main", arguments) {
+                VariableType::Void if main.type_annotation.is_some() => VariableType::Number(0),
+                value => value,
+            };
+            self.last_value = Some(value.clone());
+            self.exit_value = Some(value);
+        }
+    }
 }
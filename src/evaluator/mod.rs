@@ -1,14 +1,92 @@
+mod captures;
 mod cpptranspiler;
 mod interpreter;
+mod resolver;
+mod session;
+mod symbols;
 mod to_cpp;
-use crate::evaluator::cpptranspiler::ASTCppTranspiler;
+mod typechecker;
+pub mod types;
+use crate::diagnostics::Diagnostic;
+use crate::evaluator::captures::ASTCaptureAnalyzer;
+use crate::evaluator::cpptranspiler::{ASTCppTranspiler, Output};
 use crate::evaluator::interpreter::ASTInterpreter;
+use crate::evaluator::resolver::ASTResolver;
+use crate::evaluator::typechecker::ASTTypeChecker;
 use crate::parser::Ast;
 
+pub use crate::evaluator::session::Session;
+pub use crate::evaluator::symbols::ModuleSymbols;
+
+pub fn resolve(ast: &Ast) -> Vec<Diagnostic> {
+    resolve_with_modules(ast, &[])
+}
+
+/// Resolves `ast` as one module of a project, whose other `modules` it can
+/// call into through their namespaces.
+pub fn resolve_with_modules(ast: &Ast, modules: &[ModuleSymbols]) -> Vec<Diagnostic> {
+    let mut resolver = ASTResolver::new();
+    resolver.declare_modules(ast, modules);
+    resolver.declare_functions(ast);
+    ast.visit(&mut resolver);
+    resolver.diagnostics
+}
+
+/// Assigns a type to every expression of `ast` and reports type errors.
+/// Declarations without an annotation pick up the type of their initializer,
+/// which the transpiler then emits instead of `auto`.
+pub fn check(ast: &Ast) -> Vec<Diagnostic> {
+    let mut checker = ASTTypeChecker::new();
+    checker.declare(ast);
+    ast.visit(&mut checker);
+    checker.diagnostics
+}
+
 pub fn cpptranspile(ast: &Ast) -> String {
+    cpptranspile_with_diagnostics(ast).0
+}
+
+pub fn cpptranspile_with_diagnostics(ast: &Ast) -> (String, Vec<Diagnostic>) {
+    ast.visit(&mut ASTCaptureAnalyzer::new());
     let mut eval = ASTCppTranspiler::new();
     ast.visit(&mut eval);
-    return eval.result;
+    return (eval.result, eval.diagnostics);
+}
+
+/// A module transpiled into a header, which declares its structs and
+/// functions for other translation units, and a source file defining them.
+pub struct CppModule {
+    pub header: String,
+    pub source: String,
+}
+
+/// Transpiles the module `name` into `name.h` and `name.cpp`. The header is
+/// guarded against multiple inclusion and the source includes it, along with
+/// the headers of the other `modules` it uses.
+pub fn cpptranspile_module(
+    ast: &Ast,
+    name: &str,
+    modules: &[ModuleSymbols],
+) -> (CppModule, Vec<Diagnostic>) {
+    let guard: String = name
+        .chars()
+        .map(|c| match c {
+            c if c.is_ascii_alphanumeric() => c.to_ascii_uppercase(),
+            _ => '_',
+        })
+        .collect();
+    ast.visit(&mut ASTCaptureAnalyzer::new());
+    let mut header = ASTCppTranspiler::with_output(Output::Header(format!("{}_H", guard)));
+    header.declare_modules(name, modules);
+    ast.visit(&mut header);
+    let mut source = ASTCppTranspiler::with_output(Output::Source(format!("{}.h", name)));
+    source.declare_modules(name, modules);
+    ast.visit(&mut source);
+    let module = CppModule {
+        header: header.result,
+        source: source.result,
+    };
+    (module, source.diagnostics)
 }
 
 pub fn interpret(ast: &Ast) -> String {
@@ -16,3 +94,13 @@ pub fn interpret(ast: &Ast) -> String {
     ast.visit(&mut eval);
     return format!("{}", eval.last_value.unwrap());
 }
+
+/// Runs a program and returns its exit code, taken from what `main` (or a
+/// top level `return`) returned. Programs returning nothing exit with 0.
+pub fn run(ast: &Ast) -> i32 {
+    let mut eval = ASTInterpreter::new();
+    ast.visit(&mut eval);
+    eval.exit_value
+        .and_then(|value| value.as_integer())
+        .map_or(0, |code| code as i32)
+}
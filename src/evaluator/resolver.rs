@@ -0,0 +1,502 @@
+use crate::diagnostics::{Diagnostic, Span};
+use crate::evaluator::symbols::{self, ModuleSymbols};
+use crate::parser::visitor::ASTVisitor;
+use crate::parser::*;
+use crate::tokenizer::TokenInfo;
+use std::collections::HashMap;
+
+#[derive(Clone)]
+struct Binding {
+    identifier: TokenInfo,
+    is_mut: bool,
+}
+
+#[derive(Clone)]
+struct Scope {
+    variables: HashMap<String, Binding>,
+    // Uses that did not resolve yet. They become use-before-declaration errors
+    // if a later `let` in this scope declares them, otherwise they move up to
+    // the enclosing scope when this one is closed.
+    unresolved: Vec<TokenInfo>,
+}
+
+impl Scope {
+    fn new() -> Self {
+        Self {
+            variables: HashMap::new(),
+            unresolved: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ASTResolver {
+    scopes: Vec<Scope>,
+    functions: HashMap<String, TokenInfo>,
+    namespace: Option<ASTNamespaceStatement>,
+    // The other modules of the project, for calls into other files.
+    modules: Vec<ModuleSymbols>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl ASTResolver {
+    pub fn new() -> Self {
+        Self {
+            scopes: vec![Scope::new()],
+            functions: HashMap::new(),
+            namespace: None,
+            modules: Vec::new(),
+            diagnostics: Vec::new(),
+        }
+    }
+
+    /// Collects the top level function declarations up front so that calls
+    /// can refer to functions declared further down in the file.
+    pub fn declare_functions(&mut self, ast: &Ast) {
+        for statement in &ast.statements {
+            if let ASTStatementKind::FuncDecl(func_decl) = &statement.kind {
+                let identifier = &func_decl.identifier;
+                if let Some(previous) = self.functions.get(&identifier.lexeme) {
+                    self.diagnostics.push(
+                        Diagnostic::error(
+                            &format!(
+                                "the function `{}` is defined multiple times",
+                                identifier.lexeme
+                            ),
+                            Span::from_token(identifier),
+                        )
+                        .with_label("redefined here")
+                        .with_secondary(
+                            Span::from_token(previous),
+                            &format!("previous definition of `{}` here", identifier.lexeme),
+                        ),
+                    );
+                } else {
+                    self.functions
+                        .insert(identifier.lexeme.clone(), identifier.clone());
+                }
+            }
+        }
+    }
+
+    /// Makes the functions other modules declare callable from this one,
+    /// qualified by their namespace or, within the same namespace, by name.
+    pub fn declare_modules(&mut self, ast: &Ast, modules: &[ModuleSymbols]) {
+        self.namespace = ast.namespace().cloned();
+        self.modules = modules.to_vec();
+    }
+
+    /// Resolves one input of an interactive session against the inputs
+    /// before it. Its declarations stay in scope for the ones after it and
+    /// replace earlier declarations of the same name.
+    pub fn resolve_input(&mut self, ast: &Ast) {
+        for statement in &ast.statements {
+            if let ASTStatementKind::FuncDecl(func_decl) = &statement.kind {
+                self.functions.remove(&func_decl.identifier.lexeme);
+            }
+        }
+        self.declare_functions(ast);
+        self.enter_scope();
+        for statement in &ast.statements {
+            self.visit_statement(statement);
+        }
+        let scope = self.scopes.pop().unwrap();
+        self.scopes[0].variables.extend(scope.variables);
+        // No later input can declare what this one used without declaring.
+        self.report_unresolved(scope.unresolved);
+        self.diagnostics.sort_by_key(|diagnostic| {
+            let span = diagnostic.span();
+            (span.start.row, span.start.col)
+        });
+    }
+
+    fn namespace_name(&self) -> String {
+        self.namespace
+            .as_ref()
+            .map(|namespace| namespace.name())
+            .unwrap_or_default()
+    }
+
+    fn resolve_call(&mut self, call_expression: &ASTCallExpression) {
+        let identifier = &call_expression.identifier;
+        // A call through a variable holding a function.
+        if call_expression.qualifier.is_empty() && self.lookup(&identifier.lexeme).is_some() {
+            return;
+        }
+        let own_namespace = self.namespace_name();
+        let namespace = if call_expression.qualifier.is_empty() {
+            own_namespace.clone()
+        } else {
+            path_name(&call_expression.qualifier)
+        };
+        if (namespace == own_namespace && self.functions.contains_key(&identifier.lexeme))
+            || symbols::function_module(&self.modules, &namespace, &identifier.lexeme).is_some()
+        {
+            return;
+        }
+        if call_expression.qualifier.is_empty() {
+            self.diagnostics.push(
+                Diagnostic::error(
+                    &format!("cannot find function `{}` in this scope", identifier.lexeme),
+                    Span::from_token(identifier),
+                )
+                .with_label("not found in this scope"),
+            );
+        } else if namespace != own_namespace && !symbols::is_namespace(&self.modules, &namespace) {
+            self.diagnostics.push(
+                Diagnostic::error(
+                    &format!("cannot find namespace `{}`", namespace),
+                    Span::from_tokens(
+                        call_expression.qualifier.first().unwrap(),
+                        call_expression.qualifier.last().unwrap(),
+                    ),
+                )
+                .with_label("no module declares this namespace"),
+            );
+        } else {
+            self.diagnostics.push(
+                Diagnostic::error(
+                    &format!(
+                        "cannot find function `{}` in namespace `{}`",
+                        identifier.lexeme, namespace
+                    ),
+                    Span::from_token(identifier),
+                )
+                .with_label(&format!("not found in `{}`", namespace)),
+            );
+        }
+    }
+
+    fn enter_scope(&mut self) {
+        self.scopes.push(Scope::new());
+    }
+
+    fn exit_scope(&mut self) {
+        let scope = self.scopes.pop().unwrap();
+        match self.scopes.last_mut() {
+            Some(parent) => parent.unresolved.extend(scope.unresolved),
+            None => self.report_unresolved(scope.unresolved),
+        }
+    }
+
+    fn report_unresolved(&mut self, usages: Vec<TokenInfo>) {
+        for usage in usages {
+            self.diagnostics.push(
+                Diagnostic::error(
+                    &format!("cannot find value `{}` in this scope", usage.lexeme),
+                    Span::from_token(&usage),
+                )
+                .with_label("not found in this scope"),
+            );
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Option<&Binding> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.variables.get(name))
+    }
+
+    fn resolve(&mut self, usage: &TokenInfo) {
+        // A function can be used as a value, like any variable.
+        if self.lookup(&usage.lexeme).is_none() && !self.functions.contains_key(&usage.lexeme) {
+            self.scopes
+                .last_mut()
+                .unwrap()
+                .unresolved
+                .push(usage.clone());
+        }
+    }
+
+    /// Resolves the variable an assignment writes to and reports an error
+    /// if it was not declared with `mut`.
+    fn resolve_assignment(&mut self, target: &TokenInfo) {
+        self.resolve(target);
+        let binding = match self.lookup(&target.lexeme) {
+            Some(binding) if !binding.is_mut => binding,
+            _ => return,
+        };
+        self.diagnostics.push(
+            Diagnostic::error(
+                &format!("cannot assign to immutable variable `{}`", target.lexeme),
+                Span::from_token(target),
+            )
+            .with_label("cannot assign to an immutable variable")
+            .with_secondary(
+                Span::from_token(&binding.identifier),
+                &format!("`{}` is declared here without `mut`", target.lexeme),
+            )
+            .with_help(&format!(
+                "consider making this binding mutable: `mut {}`",
+                target.lexeme
+            )),
+        );
+    }
+
+    /// Walks a place expression down to the variable it is rooted in,
+    /// resolving any indices on the way, and resolves the assignment to it.
+    fn resolve_place(&mut self, place: &ASTExpression) {
+        let mut object = place;
+        loop {
+            match &object.kind {
+                ASTExpressionKind::FieldAccess(field_access) => object = &field_access.object,
+                ASTExpressionKind::ArrayIndex(array_index) => {
+                    self.visit_expression(&array_index.index);
+                    object = &array_index.array;
+                }
+                ASTExpressionKind::Variable(variable) => {
+                    self.resolve_assignment(&variable.identifier);
+                    return;
+                }
+                _ => {
+                    self.visit_expression(object);
+                    return;
+                }
+            }
+        }
+    }
+
+    fn declare(&mut self, identifier: &TokenInfo, is_mut: bool) {
+        let name = &identifier.lexeme;
+        let (current, outer) = self.scopes.split_last_mut().unwrap();
+
+        if let Some(Binding {
+            identifier: previous,
+            ..
+        }) = current.variables.get(name)
+        {
+            self.diagnostics.push(
+                Diagnostic::error(
+                    &format!("the name `{}` is defined multiple times", name),
+                    Span::from_token(identifier),
+                )
+                .with_label("redefined here")
+                .with_secondary(
+                    Span::from_token(previous),
+                    &format!("previous definition of `{}` here", name),
+                ),
+            );
+        } else if let Some(Binding {
+            identifier: previous,
+            ..
+        }) = outer
+            .iter()
+            .rev()
+            .find_map(|scope| scope.variables.get(name))
+        {
+            self.diagnostics.push(
+                Diagnostic::warning(
+                    &format!("`{}` shadows a variable from an outer scope", name),
+                    Span::from_token(identifier),
+                )
+                .with_label("shadows the outer declaration")
+                .with_secondary(
+                    Span::from_token(previous),
+                    &format!("`{}` is first declared here", name),
+                ),
+            );
+        }
+
+        let (early, unresolved) = current
+            .unresolved
+            .drain(..)
+            .partition(|usage| &usage.lexeme == name);
+        current.unresolved = unresolved;
+        for usage in early {
+            self.diagnostics.push(
+                Diagnostic::error(
+                    &format!("cannot use `{}` before its declaration", name),
+                    Span::from_token(&usage),
+                )
+                .with_label("used here")
+                .with_secondary(
+                    Span::from_token(identifier),
+                    &format!("`{}` is declared here", name),
+                ),
+            );
+        }
+
+        current.variables.insert(
+            name.clone(),
+            Binding {
+                identifier: identifier.clone(),
+                is_mut,
+            },
+        );
+    }
+
+    fn visit_function(&mut self, parameters: &[FuncDeclParameter], body: &ASTStatement) {
+        self.enter_scope();
+        for parameter in parameters {
+            self.declare(&parameter.identifier, parameter.is_mut);
+        }
+        // Parameters live in the same scope as the outermost block of the body.
+        match &body.kind {
+            ASTStatementKind::Block(block) => {
+                for statement in &block.statements {
+                    self.visit_statement(statement);
+                }
+            }
+            _ => self.visit_statement(body),
+        }
+        self.exit_scope();
+    }
+
+    fn visit_scoped_statement(&mut self, statement: &ASTStatement) {
+        match &statement.kind {
+            ASTStatementKind::Block(block) => self.visit_block_statement(block),
+            _ => {
+                self.enter_scope();
+                self.visit_statement(statement);
+                self.exit_scope();
+            }
+        }
+    }
+}
+
+impl ASTVisitor<'_> for ASTResolver {
+    fn visit_func_decl_statement(&mut self, func_decl_statement: &ASTFuncDeclStatement) {
+        self.visit_function(&func_decl_statement.parameters, &func_decl_statement.body);
+    }
+
+    fn visit_closure_expression(&mut self, closure_expression: &ASTClosureExpression) {
+        self.visit_function(&closure_expression.parameters, &closure_expression.body);
+    }
+
+    fn visit_for_statement(&mut self, for_statement: &ASTForStatement) {
+        self.visit_expression(&for_statement.iterable);
+        self.enter_scope();
+        self.declare(&for_statement.identifier, false);
+        self.visit_scoped_statement(&for_statement.body);
+        self.exit_scope();
+    }
+
+    fn visit_while_statement(&mut self, while_statement: &ASTWhileStatement) {
+        self.visit_expression(&while_statement.condition);
+        self.visit_scoped_statement(&while_statement.body);
+    }
+
+    fn visit_if_statement(&mut self, if_statement: &ASTIfStatement) {
+        self.visit_expression(&if_statement.condition);
+        self.visit_scoped_statement(&if_statement.then_branch);
+        if let Some(else_branch) = &if_statement.else_branch {
+            self.visit_scoped_statement(&else_branch.else_statement);
+        }
+    }
+
+    fn visit_match_statement(&mut self, match_statement: &ASTMatchStatement) {
+        self.visit_expression(&match_statement.scrutinee);
+        for arm in &match_statement.arms {
+            self.enter_scope();
+            match &arm.pattern {
+                ASTMatchPattern::Literal(expr) => self.visit_expression(expr),
+                ASTMatchPattern::Range(expr) => self.visit_range_expression(expr),
+                ASTMatchPattern::Binding(identifier) => self.declare(identifier, false),
+                ASTMatchPattern::Wildcard(_) => {}
+            }
+            self.visit_scoped_statement(&arm.body);
+            self.exit_scope();
+        }
+        if let Some(else_branch) = &match_statement.else_branch {
+            self.visit_scoped_statement(&else_branch.else_statement);
+        }
+    }
+
+    fn visit_block_statement(&mut self, block_statement: &ASTBlockStatement) {
+        self.enter_scope();
+        for statement in &block_statement.statements {
+            self.visit_statement(statement);
+        }
+        self.exit_scope();
+    }
+
+    fn visit_let_statement(&mut self, let_statement: &ASTLetStatement) {
+        self.visit_expression(&let_statement.initializer);
+        self.declare(&let_statement.identifier, let_statement.is_mut);
+    }
+
+    fn visit_namespace_statement(&mut self, namespace_statement: &ASTNamespaceStatement) {
+        let keyword = &namespace_statement.namespace_keyword;
+        if let Some(namespace) = &self.namespace {
+            if namespace.namespace_keyword.start_position == keyword.start_position {
+                return;
+            }
+        }
+        self.diagnostics.push(
+            Diagnostic::error(
+                "`namespace` declarations must come first in the file",
+                Span::from_token(keyword),
+            )
+            .with_label("not at the top of the file")
+            .with_help("a namespace applies to the whole file; move it to the first line"),
+        );
+    }
+
+    fn visit_call_expression(&mut self, call_expression: &ASTCallExpression) {
+        self.resolve_call(call_expression);
+        for argument in &call_expression.arguments {
+            self.visit_expression(argument);
+        }
+    }
+
+    fn visit_assignment_expression(&mut self, assignment_expression: &ASTAssignmentExpression) {
+        self.visit_expression(&assignment_expression.expression);
+        self.resolve_assignment(&assignment_expression.identifier);
+    }
+
+    fn visit_array_assignment_expression(
+        &mut self,
+        array_assignment_expression: &ASTArrayAssignmentExpression,
+    ) {
+        self.visit_expression(&array_assignment_expression.index_expression);
+        self.visit_expression(&array_assignment_expression.expression);
+        self.resolve_assignment(&array_assignment_expression.identifier);
+    }
+
+    fn visit_field_assignment_expression(
+        &mut self,
+        field_assignment_expression: &ASTFieldAssignmentExpression,
+    ) {
+        self.visit_expression(&field_assignment_expression.expression);
+        self.resolve_place(&field_assignment_expression.field_access.object);
+    }
+
+    fn visit_compound_assignment_expression(
+        &mut self,
+        compound_assignment_expression: &ASTCompoundAssignmentExpression,
+    ) {
+        self.visit_expression(&compound_assignment_expression.expression);
+        self.resolve_place(&compound_assignment_expression.target);
+    }
+
+    fn visit_variable_expression(&mut self, variable_expression: &ASTVariableExpression) {
+        self.resolve(&variable_expression.identifier);
+    }
+
+    fn visit_number_expression(&mut self, _number: &ASTNumberExpression) {}
+
+    fn visit_string_expression(&mut self, _string: &ASTStringExpression) {}
+
+    fn visit_boolean_expression(&mut self, _boolean: &ASTBooleanExpression) {}
+
+    fn visit_unary_expression(&mut self, unary_expression: &ASTUnaryExpression) {
+        self.visit_expression(&unary_expression.operand);
+    }
+
+    fn visit_type_annotation_expression(
+        &mut self,
+        _type_annotation_expression: &ASTTypeAnnotationExpression,
+    ) {
+    }
+
+    fn finalize(&mut self) {
+        while !self.scopes.is_empty() {
+            self.exit_scope();
+        }
+        self.diagnostics.sort_by_key(|diagnostic| {
+            let span = diagnostic.span();
+            (span.start.row, span.start.col)
+        });
+    }
+}
@@ -0,0 +1,143 @@
+use crate::diagnostics::Diagnostic;
+use crate::evaluator::cpptranspile;
+use crate::evaluator::interpreter::{ASTInterpreter, VariableType};
+use crate::evaluator::resolver::ASTResolver;
+use crate::evaluator::to_cpp;
+use crate::evaluator::typechecker::ASTTypeChecker;
+use crate::parser::visitor::ASTVisitor;
+use crate::parser::{self, ASTStatementKind, Ast};
+use crate::tokenizer;
+use std::panic::{self, AssertUnwindSafe};
+
+/// Interpreter state that is kept alive between inputs, e.g. in the REPL.
+pub struct Session {
+    interpreter: ASTInterpreter,
+    resolver: ASTResolver,
+    checker: ASTTypeChecker,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Session {
+    pub fn new() -> Self {
+        Self {
+            interpreter: ASTInterpreter::new(),
+            resolver: ASTResolver::new(),
+            checker: ASTTypeChecker::new(),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Resolves, type checks and runs `source`, returning the value of its
+    /// last expression statement or `return`.
+    pub fn eval(&mut self, source: &str) -> Result<Option<String>, Vec<Diagnostic>> {
+        let ast = Self::parse(source)?;
+        let mut resolver = self.resolver.clone();
+        resolver.resolve_input(&ast);
+        if resolver.diagnostics.iter().any(Diagnostic::is_error) {
+            return Err(resolver.diagnostics);
+        }
+        resolver.diagnostics.clear();
+
+        let mut checker = self.checker.clone();
+        for statement in &ast.statements {
+            checker.visit_statement(statement);
+        }
+        if checker.diagnostics.iter().any(Diagnostic::is_error) {
+            return Err(checker.diagnostics);
+        }
+        checker.diagnostics.clear();
+        self.resolver = resolver;
+        self.checker = checker;
+
+        // The interpreter reports runtime errors by panicking, which become
+        // diagnostics here, so the default hook would only print them twice.
+        let hook = panic::take_hook();
+        panic::set_hook(Box::new(|_| {}));
+        let value = self.execute(&ast);
+        panic::set_hook(hook);
+
+        Ok(match value? {
+            Some(VariableType::Void) | None => None,
+            Some(value) => Some(value.to_string()),
+        })
+    }
+
+    fn execute(&mut self, ast: &Ast) -> Result<Option<VariableType>, Vec<Diagnostic>> {
+        let mut value = None;
+        for statement in &ast.statements {
+            let interpreter = &mut self.interpreter;
+            match Self::catch_panic(|| interpreter.execute(statement)) {
+                Ok(result) => value = result,
+                Err(diagnostics) => {
+                    // The statement was cut short, so drop whatever state it
+                    // left behind in the middle of a call.
+                    self.interpreter.unwind();
+                    return Err(diagnostics);
+                }
+            }
+        }
+        Ok(value)
+    }
+
+    /// Runs `run`, turning a panic into an error so that the session
+    /// survives it.
+    fn catch_panic<T>(run: impl FnOnce() -> T) -> Result<T, Vec<Diagnostic>> {
+        panic::catch_unwind(AssertUnwindSafe(run)).map_err(|error| {
+            let message = error
+                .downcast_ref::<String>()
+                .cloned()
+                .or_else(|| error.downcast_ref::<&str>().map(|s| s.to_string()))
+                .unwrap_or_else(|| String::from("evaluation failed"));
+            vec![Diagnostic::error(&message, Default::default())]
+        })
+    }
+
+    /// The type of an expression, given the declarations made so far.
+    pub fn type_of(&mut self, source: &str) -> Result<String, Vec<Diagnostic>> {
+        let ast = Self::parse(source)?;
+        let mut checker = self.checker.clone();
+        let mut ty = None;
+        for statement in &ast.statements {
+            match &statement.kind {
+                ASTStatementKind::Expression(expression) => ty = Some(checker.type_of(expression)),
+                _ => checker.visit_statement(statement),
+            }
+        }
+        if checker.diagnostics.iter().any(Diagnostic::is_error) {
+            return Err(checker.diagnostics);
+        }
+        Ok(ty.map(|ty| ty.to_string()).unwrap_or_default())
+    }
+
+    /// The C++ the transpiler emits for `source`, with the types of earlier
+    /// declarations filled in.
+    pub fn cpp(&mut self, source: &str) -> Result<String, Vec<Diagnostic>> {
+        let ast = Self::parse(source)?;
+        let mut checker = self.checker.clone();
+        for statement in &ast.statements {
+            checker.visit_statement(statement);
+        }
+        if let Err(error) = to_cpp::init_types() {
+            return Err(vec![Diagnostic::error(
+                &format!("cannot load the C++ types from `types.csv`: {}", error),
+                Default::default(),
+            )]);
+        }
+        Self::catch_panic(|| cpptranspile(&ast))
+    }
+
+    fn parse(source: &str) -> Result<Ast, Vec<Diagnostic>> {
+        let tokens = tokenizer::tokenize(std::io::Cursor::new(source.as_bytes()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| vec![Diagnostic::from(&error)])?;
+        parser::parse(tokens).map_err(|errors| errors.iter().map(Diagnostic::from).collect())
+    }
+}
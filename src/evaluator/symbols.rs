@@ -0,0 +1,68 @@
+use crate::parser::{ASTStatementKind, Ast};
+use std::collections::HashSet;
+
+/// What a module declares for the other modules of a project: the namespace
+/// its file is in and the functions and structs defined there.
+#[derive(Debug, Clone, Default)]
+pub struct ModuleSymbols {
+    pub module: String,
+    /// The file-scoped namespace, empty for the global namespace.
+    pub namespace: String,
+    pub functions: HashSet<String>,
+    pub structs: HashSet<String>,
+}
+
+impl ModuleSymbols {
+    pub fn collect(ast: &Ast, module: &str) -> Self {
+        let mut symbols = Self {
+            module: module.to_string(),
+            namespace: ast
+                .namespace()
+                .map(|namespace| namespace.name())
+                .unwrap_or_default(),
+            ..Default::default()
+        };
+        for statement in &ast.statements {
+            match &statement.kind {
+                ASTStatementKind::FuncDecl(func_decl) => {
+                    symbols
+                        .functions
+                        .insert(func_decl.identifier.lexeme.clone());
+                }
+                ASTStatementKind::Struct(struct_statement) => {
+                    symbols
+                        .structs
+                        .insert(struct_statement.identifier.lexeme.clone());
+                }
+                _ => {}
+            }
+        }
+        symbols
+    }
+}
+
+/// The module declaring the function `name` in `namespace`.
+pub fn function_module<'a>(
+    modules: &'a [ModuleSymbols],
+    namespace: &str,
+    name: &str,
+) -> Option<&'a ModuleSymbols> {
+    modules
+        .iter()
+        .find(|module| module.namespace == namespace && module.functions.contains(name))
+}
+
+/// The module declaring the struct `name` in `namespace`.
+pub fn struct_module<'a>(
+    modules: &'a [ModuleSymbols],
+    namespace: &str,
+    name: &str,
+) -> Option<&'a ModuleSymbols> {
+    modules
+        .iter()
+        .find(|module| module.namespace == namespace && module.structs.contains(name))
+}
+
+pub fn is_namespace(modules: &[ModuleSymbols], namespace: &str) -> bool {
+    modules.iter().any(|module| module.namespace == namespace)
+}
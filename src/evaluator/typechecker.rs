@@ -0,0 +1,965 @@
+use crate::diagnostics::{Diagnostic, Span};
+use crate::evaluator::types::{Int, Type};
+use crate::parser::visitor::ASTVisitor;
+use crate::parser::*;
+use crate::tokenizer::{NumberValue, TokenInfo};
+use std::collections::HashMap;
+
+#[derive(Clone)]
+pub struct ASTTypeChecker {
+    last_type: Type,
+    scopes: Vec<HashMap<String, Type>>,
+    functions: HashMap<String, Type>,
+    structs: HashMap<String, Vec<(String, Type)>>,
+    namespace: String,
+    // Generic functions by name, instantiated anew at every call.
+    generic_functions: HashMap<String, ASTFuncDeclStatement>,
+    // What the type parameters of the generic function being checked or
+    // called stand for.
+    type_arguments: HashMap<String, Type>,
+    // Declared return type of each function being checked, innermost last.
+    return_types: Vec<Type>,
+    // Whether the number literal being checked is the operand of a unary
+    // `-`, which lets a signed literal reach the magnitude of its minimum.
+    negated_literal: bool,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl ASTTypeChecker {
+    pub fn new() -> Self {
+        Self {
+            last_type: Type::Unknown,
+            scopes: vec![HashMap::new()],
+            functions: HashMap::new(),
+            structs: HashMap::new(),
+            namespace: String::new(),
+            generic_functions: HashMap::new(),
+            type_arguments: HashMap::new(),
+            return_types: Vec::new(),
+            negated_literal: false,
+            diagnostics: Vec::new(),
+        }
+    }
+
+    /// Collects struct and function signatures up front so that they can be
+    /// used before the statement declaring them.
+    pub fn declare(&mut self, ast: &Ast) {
+        self.namespace = ast
+            .namespace()
+            .map(|namespace| namespace.name())
+            .unwrap_or_default();
+        for statement in &ast.statements {
+            if let ASTStatementKind::Struct(struct_statement) = &statement.kind {
+                self.structs
+                    .insert(struct_statement.identifier.lexeme.clone(), Vec::new());
+            }
+        }
+        for statement in &ast.statements {
+            match &statement.kind {
+                ASTStatementKind::Struct(struct_statement) => {
+                    self.visit_struct_statement(struct_statement)
+                }
+                ASTStatementKind::FuncDecl(func_decl) => {
+                    if !func_decl.generics.is_empty() {
+                        self.generic_functions
+                            .insert(func_decl.identifier.lexeme.clone(), func_decl.clone());
+                    }
+                    let function_type =
+                        self.instantiate(func_decl, Self::unknown_type_arguments(func_decl));
+                    self.functions
+                        .insert(func_decl.identifier.lexeme.clone(), function_type);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn function_type(&self, func_decl: &ASTFuncDeclStatement) -> Type {
+        let parameters = func_decl
+            .parameters
+            .iter()
+            .map(|parameter| self.optional_annotation_type(&parameter.type_annotation))
+            .collect();
+        let return_type = match &func_decl.type_annotation {
+            Some(type_annotation) => self.annotation_type(type_annotation),
+            None => Type::Unknown,
+        };
+        Type::Function(parameters, Box::new(return_type))
+    }
+
+    /// The type of a generic function with its type parameters standing for
+    /// `type_arguments`.
+    fn instantiate(
+        &mut self,
+        func_decl: &ASTFuncDeclStatement,
+        type_arguments: HashMap<String, Type>,
+    ) -> Type {
+        let outer = std::mem::replace(&mut self.type_arguments, type_arguments);
+        let function_type = self.function_type(func_decl);
+        self.type_arguments = outer;
+        function_type
+    }
+
+    fn unknown_type_arguments(func_decl: &ASTFuncDeclStatement) -> HashMap<String, Type> {
+        func_decl
+            .generics
+            .iter()
+            .map(|generic| (generic.lexeme.clone(), Type::Unknown))
+            .collect()
+    }
+
+    /// Works out what the type parameters of a generic function stand for at
+    /// a call, from the explicit `::<...>` or else from the arguments passed
+    /// where a parameter's type is just a type parameter.
+    fn infer_type_arguments(
+        &mut self,
+        func_decl: &ASTFuncDeclStatement,
+        call_expression: &ASTCallExpression,
+        argument_types: &[Type],
+    ) -> HashMap<String, Type> {
+        let mut type_arguments = Self::unknown_type_arguments(func_decl);
+        if !call_expression.generic_arguments.is_empty() {
+            for (generic, argument) in func_decl
+                .generics
+                .iter()
+                .zip(&call_expression.generic_arguments)
+            {
+                let ty = self.annotation_type(argument);
+                type_arguments.insert(generic.lexeme.clone(), ty);
+            }
+            return type_arguments;
+        }
+        for (parameter, argument_type) in func_decl.parameters.iter().zip(argument_types) {
+            let name = match parameter.type_annotation.as_ref().map(|t| &t.kind) {
+                Some(ASTExpressionKind::TypeAnnotation(annotation))
+                    if annotation.qualifier.is_empty() && annotation.generics.is_empty() =>
+                {
+                    &annotation.base.lexeme
+                }
+                _ => continue,
+            };
+            if type_arguments.get(name) == Some(&Type::Unknown) {
+                type_arguments.insert(name.clone(), argument_type.clone());
+            }
+        }
+        type_arguments
+    }
+
+    fn check_generic_argument_count(&mut self, call_expression: &ASTCallExpression) {
+        let expected = self
+            .generic_functions
+            .get(&call_expression.identifier.lexeme)
+            .map_or(0, |func_decl| func_decl.generics.len());
+        let found = call_expression.generic_arguments.len();
+        if found == 0 || found == expected {
+            return;
+        }
+        self.diagnostics.push(
+            Diagnostic::error(
+                &format!(
+                    "this function takes {} generic arguments but {} were supplied",
+                    expected, found
+                ),
+                Span::from_token(&call_expression.identifier),
+            )
+            .with_label(&format!("expected {} generic arguments", expected)),
+        );
+    }
+
+    fn optional_annotation_type(&self, type_annotation: &Option<ASTExpression>) -> Type {
+        match type_annotation {
+            Some(type_annotation) => self.annotation_type(type_annotation),
+            None => Type::Unknown,
+        }
+    }
+
+    /// Whether a path names something in another namespace, declared in
+    /// another module whose types are not known here.
+    fn is_foreign(&self, qualifier: &[TokenInfo]) -> bool {
+        !qualifier.is_empty() && path_name(qualifier) != self.namespace
+    }
+
+    fn annotation_type(&self, type_annotation: &ASTExpression) -> Type {
+        match &type_annotation.kind {
+            ASTExpressionKind::TypeAnnotation(expr) if self.is_foreign(&expr.qualifier) => {
+                Type::Unknown
+            }
+            ASTExpressionKind::TypeAnnotation(expr) => {
+                let generics: Vec<Type> = expr
+                    .generics
+                    .iter()
+                    .map(|generic| self.annotation_type(generic))
+                    .collect();
+                match (expr.base.lexeme.as_str(), generics.as_slice()) {
+                    ("Array", [element]) => Type::Array(Box::new(element.clone())),
+                    ("Array", _) => Type::Array(Box::new(Type::Unknown)),
+                    _ => self.named_type(&expr.base),
+                }
+            }
+            ASTExpressionKind::FunctionType(expr) => {
+                let parameters = expr
+                    .parameters
+                    .iter()
+                    .map(|parameter| self.annotation_type(parameter))
+                    .collect();
+                let return_type = match &expr.return_type {
+                    Some(return_type) => self.annotation_type(return_type),
+                    None => Type::Void,
+                };
+                Type::Function(parameters, Box::new(return_type))
+            }
+            _ => Type::Unknown,
+        }
+    }
+
+    fn named_type(&self, name: &TokenInfo) -> Type {
+        if let Some(ty) = self.type_arguments.get(&name.lexeme) {
+            return ty.clone();
+        }
+        if let Some(primitive) = Type::from_name(&name.lexeme) {
+            return primitive;
+        }
+        if self.structs.contains_key(&name.lexeme) {
+            return Type::Struct(name.lexeme.clone());
+        }
+        Type::Unknown
+    }
+
+    pub fn type_of(&mut self, expression: &ASTExpression) -> Type {
+        self.visit_expression(expression);
+        self.last_type.clone()
+    }
+
+    fn enter_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    fn exit_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    fn define(&mut self, name: &str, ty: Type) {
+        self.scopes.last_mut().unwrap().insert(name.to_string(), ty);
+    }
+
+    fn lookup(&self, name: &str) -> Type {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .or_else(|| self.functions.get(name))
+            .cloned()
+            .unwrap_or(Type::Unknown)
+    }
+
+    /// Whether `expression` is a literal without a suffix that takes on type
+    /// `ty` from its context. Integer literals adapt to any numeric type, the
+    /// same way C++ converts them implicitly, and float literals to floats.
+    fn adapts(expression: &ASTExpression, ty: &Type) -> bool {
+        match expression.number_literal() {
+            Some(number) if number.suffix.is_none() => match number.value {
+                NumberValue::Int(_) => ty.is_numeric(),
+                NumberValue::Float(_) => matches!(ty, Type::Float(_) | Type::Unknown),
+            },
+            _ => false,
+        }
+    }
+
+    /// Checks that `expression` can be stored in a slot of type `expected`.
+    fn expect(&mut self, expected: &Type, expression: &ASTExpression, found: &Type) {
+        if expected.accepts(found) || Self::adapts(expression, expected) {
+            return;
+        }
+        if let (Type::Array(element), ASTExpressionKind::Array(array)) =
+            (expected, &expression.kind)
+        {
+            for item in &array.elements {
+                let item_type = item.ty().unwrap_or(Type::Unknown);
+                self.expect(element, item, &item_type);
+            }
+            expression.set_ty(expected.clone());
+            return;
+        }
+        self.diagnostics.push(
+            Diagnostic::error("mismatched types", Span::from_expression(expression))
+                .with_label(&format!("expected `{}`, found `{}`", expected, found)),
+        );
+    }
+
+    /// Checks that the condition of an `if` or `while` is a `bool`. Unlike
+    /// C++, numbers are not converted implicitly.
+    fn expect_condition(&mut self, condition: &ASTExpression) {
+        let found = self.type_of(condition);
+        self.expect(&Type::Bool, condition, &found);
+    }
+
+    /// Checks that `main` has a signature the C++ `main` can be built from:
+    /// no parameters or `args: Array<String>`, and an `i32` result if any.
+    fn check_main_signature(&mut self, func_decl: &ASTFuncDeclStatement, ty: &Type) {
+        let (parameters, return_type) = match ty {
+            Type::Function(parameters, return_type) => (parameters, return_type),
+            _ => return,
+        };
+        let args = Type::Array(Box::new(Type::String));
+        let valid_parameters = match parameters.as_slice() {
+            [] => true,
+            [parameter] => parameter == &args,
+            _ => false,
+        };
+        let valid_return = func_decl.type_annotation.is_none()
+            || matches!(return_type.as_ref(), Type::Int(Int::I32));
+        if valid_parameters && valid_return {
+            return;
+        }
+        self.diagnostics.push(
+            Diagnostic::error(
+                "`main` function has an invalid signature",
+                Span::from_token(&func_decl.identifier),
+            )
+            .with_label("invalid `main` signature")
+            .with_help("`main` takes no parameters or `args: Array<String>` and returns `i32`"),
+        );
+    }
+
+    fn visit_scoped_statement(&mut self, statement: &ASTStatement) {
+        self.enter_scope();
+        self.visit_statement(statement);
+        self.exit_scope();
+    }
+
+    /// The type of `left_expression operator right_expression`, shared by
+    /// binary expressions and compound assignments.
+    fn binary_type(
+        &mut self,
+        left_expression: &ASTExpression,
+        operator: &ASTBinaryOperator,
+        right_expression: &ASTExpression,
+    ) -> Type {
+        let left = self.type_of(left_expression);
+        let right = self.type_of(right_expression);
+        let left_literal = Self::adapts(left_expression, &right);
+        let right_literal = Self::adapts(right_expression, &left);
+
+        let result = match operator.kind {
+            ASTBinaryOperatorKind::Addition if left == Type::String && right == Type::String => {
+                Some(Type::String)
+            }
+            ASTBinaryOperatorKind::Addition
+            | ASTBinaryOperatorKind::Subtraction
+            | ASTBinaryOperatorKind::Star
+            | ASTBinaryOperatorKind::Division => {
+                if left.is_numeric() && right.is_numeric() {
+                    Some(match (left_literal, right_literal) {
+                        (true, false) => right.clone(),
+                        (false, true) => left.clone(),
+                        _ => left.common_numeric(&right),
+                    })
+                } else {
+                    None
+                }
+            }
+            ASTBinaryOperatorKind::Modulo
+            | ASTBinaryOperatorKind::BwAnd
+            | ASTBinaryOperatorKind::BwOr
+            | ASTBinaryOperatorKind::BwXor => {
+                if left.is_integer() && right.is_integer() {
+                    Some(match (left_literal, right_literal) {
+                        (true, false) => right.clone(),
+                        (false, true) => left.clone(),
+                        _ => left.common_numeric(&right),
+                    })
+                } else {
+                    None
+                }
+            }
+            ASTBinaryOperatorKind::BwShl | ASTBinaryOperatorKind::BwShr => {
+                if left.is_integer() && right.is_integer() {
+                    Some(left.clone())
+                } else {
+                    None
+                }
+            }
+            ASTBinaryOperatorKind::Equals | ASTBinaryOperatorKind::Inequal => {
+                if (left.is_numeric() && right.is_numeric())
+                    || left.accepts(&right)
+                    || right.accepts(&left)
+                {
+                    Some(Type::Bool)
+                } else {
+                    None
+                }
+            }
+            ASTBinaryOperatorKind::LowerThan
+            | ASTBinaryOperatorKind::GreaterThan
+            | ASTBinaryOperatorKind::LowerEquals
+            | ASTBinaryOperatorKind::GreaterEquals => {
+                if (left.is_numeric() && right.is_numeric())
+                    || (left == Type::String && right == Type::String)
+                    || (left == Type::Char && right == Type::Char)
+                {
+                    Some(Type::Bool)
+                } else {
+                    None
+                }
+            }
+            ASTBinaryOperatorKind::And | ASTBinaryOperatorKind::Or => {
+                if Type::Bool.accepts(&left) && Type::Bool.accepts(&right) {
+                    Some(Type::Bool)
+                } else {
+                    None
+                }
+            }
+        };
+
+        match result {
+            Some(ty) => ty,
+            None => {
+                let lexeme = &operator.token.lexeme;
+                let message = match operator.kind {
+                    ASTBinaryOperatorKind::Addition => {
+                        format!("cannot add `{}` to `{}`", right, left)
+                    }
+                    ASTBinaryOperatorKind::Subtraction => {
+                        format!("cannot subtract `{}` from `{}`", right, left)
+                    }
+                    ASTBinaryOperatorKind::Star => {
+                        format!("cannot multiply `{}` by `{}`", left, right)
+                    }
+                    ASTBinaryOperatorKind::Division => {
+                        format!("cannot divide `{}` by `{}`", left, right)
+                    }
+                    ASTBinaryOperatorKind::Modulo => format!(
+                        "cannot calculate the remainder of `{}` divided by `{}`",
+                        left, right
+                    ),
+                    _ => format!("cannot apply `{}` to `{}` and `{}`", lexeme, left, right),
+                };
+                self.diagnostics.push(
+                    Diagnostic::error(&message, Span::from_token(&operator.token)).with_label(
+                        &format!("no implementation for `{} {} {}`", left, lexeme, right),
+                    ),
+                );
+                Type::Unknown
+            }
+        }
+    }
+
+    fn call_type(
+        &mut self,
+        function_type: Type,
+        identifier: &TokenInfo,
+        arguments: &[ASTExpression],
+        argument_types: &[Type],
+    ) -> Type {
+        match function_type {
+            Type::Function(parameters, return_type) => {
+                if parameters.len() != arguments.len() {
+                    self.diagnostics.push(
+                        Diagnostic::error(
+                            &format!(
+                                "this function takes {} arguments but {} were supplied",
+                                parameters.len(),
+                                arguments.len()
+                            ),
+                            Span::from_token(identifier),
+                        )
+                        .with_label(&format!("expected {} arguments", parameters.len())),
+                    );
+                } else {
+                    for ((parameter, argument), argument_type) in
+                        parameters.iter().zip(arguments).zip(argument_types)
+                    {
+                        self.expect(parameter, argument, argument_type);
+                    }
+                }
+                *return_type
+            }
+            Type::Unknown => Type::Unknown,
+            other => {
+                self.diagnostics.push(
+                    Diagnostic::error(
+                        &format!("expected function, found `{}`", other),
+                        Span::from_token(identifier),
+                    )
+                    .with_label("not a function"),
+                );
+                Type::Unknown
+            }
+        }
+    }
+
+    fn field_type(&mut self, object: &Type, field: &TokenInfo) -> Type {
+        match object {
+            Type::Struct(name) => {
+                let found = self.structs.get(name).and_then(|fields| {
+                    fields
+                        .iter()
+                        .find(|(field_name, _)| field_name == &field.lexeme)
+                        .map(|(_, ty)| ty.clone())
+                });
+                match found {
+                    Some(ty) => ty,
+                    None => {
+                        self.diagnostics.push(
+                            Diagnostic::error(
+                                &format!("no field `{}` on type `{}`", field.lexeme, name),
+                                Span::from_token(field),
+                            )
+                            .with_label("unknown field"),
+                        );
+                        Type::Unknown
+                    }
+                }
+            }
+            Type::Unknown => Type::Unknown,
+            other => {
+                self.diagnostics.push(
+                    Diagnostic::error(
+                        &format!("`{}` is a primitive type and has no fields", other),
+                        Span::from_token(field),
+                    )
+                    .with_label("unknown field"),
+                );
+                Type::Unknown
+            }
+        }
+    }
+
+    fn index_type(&mut self, array: &Type, index: &ASTExpression) -> Type {
+        let index_type = self.type_of(index);
+        if !index_type.is_integer() {
+            self.diagnostics.push(
+                Diagnostic::error("mismatched types", Span::from_expression(index)).with_label(
+                    &format!("expected an integer index, found `{}`", index_type),
+                ),
+            );
+        }
+        match array {
+            Type::Array(element) => *element.clone(),
+            Type::Unknown => Type::Unknown,
+            other => {
+                self.diagnostics.push(
+                    Diagnostic::error(
+                        &format!("cannot index into a value of type `{}`", other),
+                        Span::from_expression(index),
+                    )
+                    .with_label("indexed here"),
+                );
+                Type::Unknown
+            }
+        }
+    }
+}
+
+impl ASTVisitor<'_> for ASTTypeChecker {
+    fn visit_expression(&mut self, expression: &ASTExpression) {
+        self.do_visit_expression(expression);
+        expression.set_ty(self.last_type.clone());
+    }
+
+    fn visit_func_decl_statement(&mut self, func_decl_statement: &ASTFuncDeclStatement) {
+        // The body of a generic function is checked once, with its type
+        // parameters unknown.
+        let outer = std::mem::replace(
+            &mut self.type_arguments,
+            Self::unknown_type_arguments(func_decl_statement),
+        );
+        let function_type = self.function_type(func_decl_statement);
+        if func_decl_statement.identifier.lexeme == "main" {
+            self.check_main_signature(func_decl_statement, &function_type);
+        }
+        self.functions.insert(
+            func_decl_statement.identifier.lexeme.clone(),
+            function_type.clone(),
+        );
+        if let Type::Function(parameters, return_type) = function_type {
+            self.enter_scope();
+            for (parameter, ty) in func_decl_statement.parameters.iter().zip(parameters) {
+                self.define(&parameter.identifier.lexeme, ty);
+            }
+            self.return_types.push(*return_type);
+            self.visit_statement(&func_decl_statement.body);
+            self.return_types.pop();
+            self.exit_scope();
+        }
+        self.type_arguments = outer;
+    }
+
+    fn visit_closure_expression(&mut self, closure_expression: &ASTClosureExpression) {
+        let parameters: Vec<Type> = closure_expression
+            .parameters
+            .iter()
+            .map(|parameter| self.optional_annotation_type(&parameter.type_annotation))
+            .collect();
+        let return_type = match &closure_expression.type_annotation {
+            Some(type_annotation) => self.annotation_type(type_annotation),
+            None => Type::Unknown,
+        };
+        self.enter_scope();
+        for (parameter, ty) in closure_expression.parameters.iter().zip(&parameters) {
+            self.define(&parameter.identifier.lexeme, ty.clone());
+        }
+        self.return_types.push(return_type.clone());
+        self.visit_statement(&closure_expression.body);
+        self.return_types.pop();
+        self.exit_scope();
+        self.last_type = Type::Function(parameters, Box::new(return_type));
+    }
+
+    fn visit_return_statement(&mut self, return_statement: &ASTReturnStatement) {
+        if let Some(expression) = &return_statement.return_value {
+            let found = self.type_of(expression);
+            if let Some(expected) = self.return_types.last().cloned() {
+                self.expect(&expected, expression, &found);
+            }
+        }
+    }
+
+    fn visit_while_statement(&mut self, while_statement: &ASTWhileStatement) {
+        self.expect_condition(&while_statement.condition);
+        self.visit_scoped_statement(&while_statement.body);
+    }
+
+    fn visit_for_statement(&mut self, for_statement: &ASTForStatement) {
+        let iterable = self.type_of(&for_statement.iterable);
+        let element = match iterable {
+            Type::Array(element) => *element,
+            _ => Type::Unknown,
+        };
+        let ty = match &for_statement.type_annotation {
+            Some(type_annotation) => {
+                let annotated = self.annotation_type(type_annotation);
+                if !(annotated.is_numeric() && element.is_numeric()) {
+                    self.expect(&annotated, &for_statement.iterable, &element);
+                }
+                annotated
+            }
+            None => element,
+        };
+        self.enter_scope();
+        self.define(&for_statement.identifier.lexeme, ty);
+        self.visit_scoped_statement(&for_statement.body);
+        self.exit_scope();
+    }
+
+    fn visit_block_statement(&mut self, block_statement: &ASTBlockStatement) {
+        self.enter_scope();
+        for statement in &block_statement.statements {
+            self.visit_statement(statement);
+        }
+        self.exit_scope();
+    }
+
+    fn visit_if_statement(&mut self, if_statement: &ASTIfStatement) {
+        self.expect_condition(&if_statement.condition);
+        self.visit_scoped_statement(&if_statement.then_branch);
+        if let Some(else_branch) = &if_statement.else_branch {
+            self.visit_scoped_statement(&else_branch.else_statement);
+        }
+    }
+
+    fn visit_match_statement(&mut self, match_statement: &ASTMatchStatement) {
+        let scrutinee = self.type_of(&match_statement.scrutinee);
+        for arm in &match_statement.arms {
+            self.enter_scope();
+            match &arm.pattern {
+                ASTMatchPattern::Literal(expr) => {
+                    let found = self.type_of(expr);
+                    self.expect(&scrutinee, expr, &found);
+                }
+                ASTMatchPattern::Range(expr) => {
+                    for bound in [&expr.start, &expr.end] {
+                        let found = self.type_of(bound);
+                        self.expect(&scrutinee, bound, &found);
+                    }
+                }
+                ASTMatchPattern::Binding(identifier) => {
+                    self.define(&identifier.lexeme, scrutinee.clone())
+                }
+                ASTMatchPattern::Wildcard(_) => {}
+            }
+            self.visit_scoped_statement(&arm.body);
+            self.exit_scope();
+        }
+        if let Some(else_branch) = &match_statement.else_branch {
+            self.visit_scoped_statement(&else_branch.else_statement);
+        }
+    }
+
+    fn visit_struct_statement(&mut self, struct_statement: &ASTStructStatement) {
+        let fields = struct_statement
+            .fields
+            .iter()
+            .map(|field| {
+                (
+                    field.identifier.lexeme.clone(),
+                    self.annotation_type(&field.type_annotation),
+                )
+            })
+            .collect();
+        self.structs
+            .insert(struct_statement.identifier.lexeme.clone(), fields);
+    }
+
+    fn visit_let_statement(&mut self, let_statement: &ASTLetStatement) {
+        let found = self.type_of(&let_statement.initializer);
+        let ty = match &let_statement.type_annotation {
+            Some(type_annotation) => {
+                let expected = self.annotation_type(type_annotation);
+                self.expect(&expected, &let_statement.initializer, &found);
+                expected
+            }
+            None => found,
+        };
+        self.define(&let_statement.identifier.lexeme, ty);
+    }
+
+    fn visit_call_expression(&mut self, call_expression: &ASTCallExpression) {
+        if self.is_foreign(&call_expression.qualifier) {
+            for argument in &call_expression.arguments {
+                self.type_of(argument);
+            }
+            self.last_type = Type::Unknown;
+            return;
+        }
+        let argument_types: Vec<Type> = call_expression
+            .arguments
+            .iter()
+            .map(|argument| self.type_of(argument))
+            .collect();
+        self.check_generic_argument_count(call_expression);
+        let name = &call_expression.identifier.lexeme;
+        let is_variable = self.scopes.iter().any(|scope| scope.contains_key(name));
+        let function_type = match self.generic_functions.get(name).cloned() {
+            Some(func_decl) if !is_variable => {
+                let type_arguments =
+                    self.infer_type_arguments(&func_decl, call_expression, &argument_types);
+                self.instantiate(&func_decl, type_arguments)
+            }
+            _ => self.lookup(name),
+        };
+        self.last_type = self.call_type(
+            function_type,
+            &call_expression.identifier,
+            &call_expression.arguments,
+            &argument_types,
+        );
+    }
+
+    fn visit_std_call_expression(&mut self, std_call_expression: &ASTStdCallExpression) {
+        for argument in &std_call_expression.arguments {
+            self.visit_expression(argument);
+        }
+        self.last_type = match std_call_expression.identifier.lexeme.as_str() {
+            "println" | "swap" => Type::Void,
+            "to_string" => Type::String,
+            _ => Type::Unknown,
+        };
+    }
+
+    fn visit_assignment_expression(&mut self, assignment_expression: &ASTAssignmentExpression) {
+        let expected = self.lookup(&assignment_expression.identifier.lexeme);
+        let found = self.type_of(&assignment_expression.expression);
+        self.expect(&expected, &assignment_expression.expression, &found);
+        self.last_type = expected;
+    }
+
+    fn visit_array_assignment_expression(
+        &mut self,
+        array_assignment_expression: &ASTArrayAssignmentExpression,
+    ) {
+        let expected = self.type_of(&array_assignment_expression.index_expression);
+        let found = self.type_of(&array_assignment_expression.expression);
+        self.expect(&expected, &array_assignment_expression.expression, &found);
+        self.last_type = expected;
+    }
+
+    fn visit_struct_literal_expression(
+        &mut self,
+        struct_literal_expression: &ASTStructLiteralExpression,
+    ) {
+        let name = &struct_literal_expression.identifier.lexeme;
+        let ty = Type::Struct(name.clone());
+        for field in &struct_literal_expression.fields {
+            let expected = self.field_type(&ty, &field.identifier);
+            let found = self.type_of(&field.expression);
+            self.expect(&expected, &field.expression, &found);
+        }
+        self.last_type = if self.structs.contains_key(name) {
+            ty
+        } else {
+            Type::Unknown
+        };
+    }
+
+    fn visit_field_access_expression(
+        &mut self,
+        field_access_expression: &ASTFieldAccessExpression,
+    ) {
+        let object = self.type_of(&field_access_expression.object);
+        self.last_type = self.field_type(&object, &field_access_expression.field);
+    }
+
+    fn visit_field_assignment_expression(
+        &mut self,
+        field_assignment_expression: &ASTFieldAssignmentExpression,
+    ) {
+        self.visit_field_access_expression(&field_assignment_expression.field_access);
+        let expected = self.last_type.clone();
+        let found = self.type_of(&field_assignment_expression.expression);
+        self.expect(&expected, &field_assignment_expression.expression, &found);
+        self.last_type = expected;
+    }
+
+    fn visit_compound_assignment_expression(
+        &mut self,
+        compound_assignment_expression: &ASTCompoundAssignmentExpression,
+    ) {
+        let found = self.binary_type(
+            &compound_assignment_expression.target,
+            &compound_assignment_expression.operator,
+            &compound_assignment_expression.expression,
+        );
+        let expected = compound_assignment_expression
+            .target
+            .ty()
+            .unwrap_or(Type::Unknown);
+        if found != Type::Unknown {
+            self.expect(
+                &expected,
+                &compound_assignment_expression.expression,
+                &found,
+            );
+        }
+        self.last_type = expected;
+    }
+
+    fn visit_range_expression(&mut self, range_expression: &ASTRangeExpression) {
+        let mut element = Type::Unknown;
+        for bound in [&range_expression.start, &range_expression.end] {
+            let ty = self.type_of(bound);
+            if !ty.is_integer() {
+                self.diagnostics.push(
+                    Diagnostic::error("mismatched types", Span::from_expression(bound))
+                        .with_label(&format!("expected an integer, found `{}`", ty)),
+                );
+            } else if !Self::adapts(bound, &ty) {
+                element = ty;
+            }
+        }
+        if element == Type::Unknown {
+            element = Type::Int(Int::I32);
+        }
+        self.last_type = Type::Array(Box::new(element));
+    }
+
+    fn visit_array_expression(&mut self, array_expression: &ASTArrayExpression) {
+        let mut element = Type::Unknown;
+        for expression in &array_expression.elements {
+            let ty = self.type_of(expression);
+            if element == Type::Unknown {
+                element = ty;
+            } else {
+                self.expect(&element.clone(), expression, &ty);
+            }
+        }
+        self.last_type = Type::Array(Box::new(element));
+    }
+
+    fn visit_array_index_expression(&mut self, array_index_expression: &ASTArrayIndexExpression) {
+        let array = self.type_of(&array_index_expression.array);
+        self.last_type = self.index_type(&array, &array_index_expression.index);
+    }
+
+    fn visit_variable_expression(&mut self, variable_expression: &ASTVariableExpression) {
+        self.last_type = self.lookup(variable_expression.identifier());
+    }
+
+    fn visit_number_expression(&mut self, number: &ASTNumberExpression) {
+        let negated = std::mem::take(&mut self.negated_literal);
+        // The tokenizer admits a signed literal as large as the magnitude of
+        // the type's minimum, which only fits when it is negated.
+        if let (NumberValue::Int(value), Some(Type::Int(int))) = (number.value, &number.suffix) {
+            if int.is_signed() && !negated && value >= 1 << (int.bits() - 1) {
+                self.diagnostics.push(
+                    Diagnostic::error(
+                        &format!("literal out of range for `{}`", number.kind()),
+                        Span::from_token(&number.num),
+                    )
+                    .with_label("does not fit"),
+                );
+            }
+        }
+        self.last_type = number.kind();
+    }
+
+    fn visit_string_expression(&mut self, string: &ASTStringExpression) {
+        self.last_type = if string.is_char() {
+            Type::Char
+        } else {
+            Type::String
+        };
+    }
+
+    fn visit_boolean_expression(&mut self, _boolean: &ASTBooleanExpression) {
+        self.last_type = Type::Bool;
+    }
+
+    fn visit_unary_expression(&mut self, unary_expression: &ASTUnaryExpression) {
+        self.negated_literal =
+            matches!(
+                unary_expression.operator.kind,
+                ASTUnaryOperatorKind::Subtraction
+            ) && matches!(unary_expression.operand.kind, ASTExpressionKind::Number(_));
+        let operand = self.type_of(&unary_expression.operand);
+        let is_valid = match unary_expression.operator.kind {
+            ASTUnaryOperatorKind::Subtraction => operand.is_numeric(),
+            ASTUnaryOperatorKind::BwNot => operand.is_integer(),
+            ASTUnaryOperatorKind::Not => Type::Bool.accepts(&operand),
+        };
+        if is_valid {
+            self.last_type = operand;
+        } else {
+            self.diagnostics.push(
+                Diagnostic::error(
+                    &format!(
+                        "cannot apply unary operator `{}` to type `{}`",
+                        unary_expression.operator.token.lexeme, operand
+                    ),
+                    Span::from_token(&unary_expression.operator.token),
+                )
+                .with_label("cannot be applied"),
+            );
+            self.last_type = Type::Unknown;
+        }
+    }
+
+    fn visit_binary_expression(&mut self, binary_expression: &ASTBinaryExpression) {
+        self.last_type = self.binary_type(
+            &binary_expression.left,
+            &binary_expression.operator,
+            &binary_expression.right,
+        );
+    }
+
+    fn visit_parenthesized_expression(
+        &mut self,
+        parenthesized_expression: &ASTParenthesizedExpression,
+    ) {
+        self.last_type = self.type_of(&parenthesized_expression.expression);
+    }
+
+    fn visit_type_annotation_expression(
+        &mut self,
+        type_annotation_expression: &ASTTypeAnnotationExpression,
+    ) {
+        self.last_type = self.named_type(&type_annotation_expression.base);
+    }
+
+    fn finalize(&mut self) {
+        self.diagnostics.sort_by_key(|diagnostic| {
+            let span = diagnostic.span();
+            (span.start.row, span.start.col)
+        });
+    }
+}
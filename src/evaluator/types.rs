@@ -0,0 +1,171 @@
+use std::fmt;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Int {
+    U8,
+    U16,
+    U32,
+    U64,
+    I8,
+    I16,
+    I32,
+    I64,
+}
+
+impl Int {
+    pub fn bits(&self) -> u32 {
+        match self {
+            Int::U8 | Int::I8 => 8,
+            Int::U16 | Int::I16 => 16,
+            Int::U32 | Int::I32 => 32,
+            Int::U64 | Int::I64 => 64,
+        }
+    }
+
+    pub fn is_signed(&self) -> bool {
+        matches!(self, Int::I8 | Int::I16 | Int::I32 | Int::I64)
+    }
+
+    /// Whether every value of `other` fits into `self`.
+    pub fn widens(&self, other: &Int) -> bool {
+        if self.is_signed() == other.is_signed() {
+            self.bits() >= other.bits()
+        } else {
+            self.is_signed() && self.bits() > other.bits()
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Float {
+    F32,
+    F64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
+    Int(Int),
+    Float(Float),
+    Bool,
+    Char,
+    String,
+    Array(Box<Type>),
+    Function(Vec<Type>, Box<Type>),
+    Struct(String),
+    Void,
+    /// The type could not be determined, e.g. for user defined types that are
+    /// not declared in the file. It is compatible with every other type so
+    /// that a single unknown does not cascade into more errors.
+    Unknown,
+}
+
+impl Type {
+    /// Maps a primitive name from `types.csv` (and `bool`) to its type.
+    pub fn from_name(name: &str) -> Option<Type> {
+        match name {
+            "u8" => Some(Type::Int(Int::U8)),
+            "u16" => Some(Type::Int(Int::U16)),
+            "u32" => Some(Type::Int(Int::U32)),
+            "u64" => Some(Type::Int(Int::U64)),
+            "i8" => Some(Type::Int(Int::I8)),
+            "i16" => Some(Type::Int(Int::I16)),
+            "i32" => Some(Type::Int(Int::I32)),
+            "i64" => Some(Type::Int(Int::I64)),
+            "f32" => Some(Type::Float(Float::F32)),
+            "f64" => Some(Type::Float(Float::F64)),
+            "bool" => Some(Type::Bool),
+            "char" => Some(Type::Char),
+            "String" => Some(Type::String),
+            _ => None,
+        }
+    }
+
+    pub fn is_integer(&self) -> bool {
+        matches!(self, Type::Int(_) | Type::Unknown)
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        matches!(self, Type::Int(_) | Type::Float(_) | Type::Unknown)
+    }
+
+    pub fn is_known(&self) -> bool {
+        match self {
+            Type::Unknown => false,
+            Type::Array(element) => element.is_known(),
+            Type::Function(parameters, return_type) => {
+                parameters.iter().all(Type::is_known) && return_type.is_known()
+            }
+            _ => true,
+        }
+    }
+
+    /// Whether a value of type `other` can be used where `self` is expected.
+    /// Numbers convert implicitly as long as no information is lost.
+    pub fn accepts(&self, other: &Type) -> bool {
+        match (self, other) {
+            (Type::Unknown, _) | (_, Type::Unknown) => true,
+            (Type::Int(expected), Type::Int(found)) => expected.widens(found),
+            (Type::Float(_), Type::Int(_)) => true,
+            (Type::Float(expected), Type::Float(found)) => {
+                expected == found || *expected == Float::F64
+            }
+            (Type::Array(expected), Type::Array(found)) => expected.accepts(found),
+            (
+                Type::Function(expected_parameters, expected_return),
+                Type::Function(found_parameters, found_return),
+            ) => {
+                expected_parameters.len() == found_parameters.len()
+                    && expected_parameters
+                        .iter()
+                        .zip(found_parameters)
+                        .all(|(expected, found)| expected.accepts(found))
+                    && expected_return.accepts(found_return)
+            }
+            _ => self == other,
+        }
+    }
+
+    /// The type both operands of an arithmetic operator are converted to,
+    /// following the usual C++ arithmetic conversions.
+    pub fn common_numeric(&self, other: &Type) -> Type {
+        match (self, other) {
+            (Type::Unknown, _) | (_, Type::Unknown) => Type::Unknown,
+            (Type::Float(left), Type::Float(right)) => {
+                if *left == Float::F64 || *right == Float::F64 {
+                    Type::Float(Float::F64)
+                } else {
+                    Type::Float(Float::F32)
+                }
+            }
+            (Type::Float(float), _) | (_, Type::Float(float)) => Type::Float(*float),
+            (Type::Int(left), Type::Int(right)) => {
+                if right.bits() > left.bits() {
+                    Type::Int(*right)
+                } else {
+                    Type::Int(*left)
+                }
+            }
+            _ => Type::Unknown,
+        }
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Type::Int(int) => write!(f, "{}", format!("{:?}", int).to_lowercase()),
+            Type::Float(float) => write!(f, "{}", format!("{:?}", float).to_lowercase()),
+            Type::Bool => write!(f, "bool"),
+            Type::Char => write!(f, "char"),
+            Type::String => write!(f, "String"),
+            Type::Array(element) => write!(f, "Array<{}>", element),
+            Type::Function(parameters, return_type) => {
+                let parameters: Vec<String> = parameters.iter().map(Type::to_string).collect();
+                write!(f, "fn({}): {}", parameters.join(", "), return_type)
+            }
+            Type::Struct(name) => write!(f, "{}", name),
+            Type::Void => write!(f, "void"),
+            Type::Unknown => write!(f, "_"),
+        }
+    }
+}
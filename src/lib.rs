@@ -1,4 +1,6 @@
 pub mod app;
+pub mod diagnostics;
 pub mod evaluator;
 pub mod parser;
+pub mod repl;
 pub mod tokenizer;
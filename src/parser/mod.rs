@@ -1,24 +1,39 @@
+use crate::diagnostics::Diagnostic;
+use crate::evaluator::types::{Float, Int, Type};
 use crate::parser::parser::Node;
 use crate::parser::visitor::ASTVisitor;
-use crate::tokenizer::{TokenInfo};
+use crate::tokenizer::{self, NumberValue, Position, Token, TokenInfo};
+use std::cell::RefCell;
 
 pub mod parser;
 pub mod visitor;
 
 pub struct Ast {
     pub statements: Vec<ASTStatement>,
+    /// Warnings about code that parsed but may not mean what it looks like.
+    pub warnings: Vec<Diagnostic>,
 }
 
 impl Ast {
     pub fn new() -> Self {
         Self {
             statements: Vec::new(),
+            warnings: Vec::new(),
         }
     }
 
     pub fn add_statement(&mut self, statement: ASTStatement) {
         self.statements.push(statement);
     }
+    /// The file-scoped namespace the statements of this file belong to,
+    /// declared by a `namespace` statement at its top.
+    pub fn namespace(&self) -> Option<&ASTNamespaceStatement> {
+        match &self.statements.first()?.kind {
+            ASTStatementKind::Namespace(namespace) => Some(namespace),
+            _ => None,
+        }
+    }
+
     pub fn visit(&self, visitor: &mut dyn ASTVisitor) {
         for statement in &self.statements {
             visitor.visit_statement(statement);
@@ -27,24 +42,63 @@ impl Ast {
     }
 }
 
-pub fn parse(tokens: Vec<TokenInfo>) -> Option<Ast> {
+pub fn parse(tokens: Vec<TokenInfo>) -> Result<Ast, Vec<ParseError>> {
     let mut ast: Ast = Ast::new();
     let mut parser = Node::new(tokens);
 
-    loop {
-        match parser.next_statement() {
-            Some(stmt) => ast.add_statement(stmt),
-            None => {
-                if parser.is_at_end() {
-                    break;
-                } else {
-                    return None;
-                }
-            }
+    while let Some(stmt) = parser.next_statement() {
+        ast.add_statement(stmt);
+    }
+
+    let errors = parser.take_errors();
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    ast.warnings = parser.take_warnings();
+
+    return Ok(ast);
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub position: Position,
+    pub expected: Vec<Token>,
+    pub found: Box<TokenInfo>,
+}
+
+impl ParseError {
+    pub fn new(expected: Vec<Token>, found: TokenInfo) -> Self {
+        Self {
+            position: found.start_position,
+            expected,
+            found: Box::new(found),
         }
     }
 
-    return Some(ast);
+    pub fn expected_description(&self) -> String {
+        let expected: Vec<String> = self.expected.iter().map(|t| format!("{:?}", t)).collect();
+        if expected.len() == 1 {
+            expected[0].clone()
+        } else {
+            format!("one of {}", expected.join(", "))
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Parser error: expected {}, found {:?} '{}' on line {}, column {}",
+            self.expected_description(),
+            self.found.token,
+            self.found.lexeme,
+            self.position.row,
+            self.position.col
+        )
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -57,6 +111,9 @@ pub enum ASTStatementKind {
     FuncDecl(ASTFuncDeclStatement),
     Return(ASTReturnStatement),
     For(ASTForStatement),
+    Match(ASTMatchStatement),
+    Struct(ASTStructStatement),
+    Namespace(ASTNamespaceStatement),
 }
 
 #[derive(Debug, Clone)]
@@ -67,19 +124,58 @@ pub struct ASTReturnStatement {
 
 #[derive(Debug, Clone)]
 pub struct ASTTypeAnnotationExpression {
+    /// The namespaces before the type name in `A::B::Type`.
+    pub qualifier: Vec<TokenInfo>,
     pub base: TokenInfo,
-    pub generics: Vec<TokenInfo>,
+    /// The type arguments, `i32` in `Array<i32>`.
+    pub generics: Vec<ASTExpression>,
 }
 
 #[derive(Debug, Clone)]
 pub struct FuncDeclParameter {
+    pub is_mut: bool,
     pub identifier: TokenInfo,
     pub type_annotation: Option<ASTExpression>,
 }
 
+#[derive(Debug, Clone)]
+pub struct ASTStructField {
+    pub identifier: TokenInfo,
+    pub type_annotation: ASTExpression,
+}
+
+#[derive(Debug, Clone)]
+pub struct ASTNamespaceStatement {
+    pub namespace_keyword: TokenInfo,
+    pub path: Vec<TokenInfo>,
+}
+
+impl ASTNamespaceStatement {
+    pub fn name(&self) -> String {
+        path_name(&self.path)
+    }
+}
+
+/// Joins the segments of a path like `A::B` with `::`.
+pub fn path_name(path: &[TokenInfo]) -> String {
+    path.iter()
+        .map(|segment| segment.lexeme.as_str())
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+#[derive(Debug, Clone)]
+pub struct ASTStructStatement {
+    pub struct_keyword: TokenInfo,
+    pub identifier: TokenInfo,
+    pub fields: Vec<ASTStructField>,
+}
+
 #[derive(Debug, Clone)]
 pub struct ASTFuncDeclStatement {
     pub identifier: TokenInfo,
+    /// The type parameters of a generic function, `T` in `fn id<T>(t: T)`.
+    pub generics: Vec<TokenInfo>,
     pub parameters: Vec<FuncDeclParameter>,
     pub type_annotation: Option<ASTExpression>,
     pub body: Box<ASTStatement>,
@@ -126,6 +222,63 @@ impl ASTForStatement {
     }
 }
 
+#[derive(Debug, Clone)]
+pub enum ASTMatchPattern {
+    Literal(Box<ASTExpression>),
+    Range(ASTRangeExpression),
+    Binding(TokenInfo),
+    Wildcard(TokenInfo),
+}
+
+impl ASTMatchPattern {
+    pub fn is_integer_literal(&self) -> bool {
+        match self {
+            ASTMatchPattern::Literal(expr) => expr.is_integer_literal(),
+            _ => false,
+        }
+    }
+
+    /// The value of an integer literal pattern, with its sign applied.
+    pub fn integer_value(&self) -> Option<i128> {
+        match self {
+            ASTMatchPattern::Literal(expr) => expr.integer_literal_value(),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ASTMatchArm {
+    pub pattern: ASTMatchPattern,
+    pub arrow: TokenInfo,
+    pub body: Box<ASTStatement>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ASTMatchStatement {
+    pub match_keyword: TokenInfo,
+    pub scrutinee: ASTExpression,
+    pub arms: Vec<ASTMatchArm>,
+    pub else_branch: Option<ASTElseStatement>,
+}
+
+impl ASTMatchStatement {
+    /// Whether the match can become a C++ `switch`: every arm is an integer
+    /// literal, and no two are equal, which would repeat a `case` label.
+    pub fn is_switchable(&self) -> bool {
+        let mut values = Vec::new();
+        self.arms
+            .iter()
+            .all(|arm| match arm.pattern.integer_value() {
+                Some(value) if !values.contains(&value) => {
+                    values.push(value);
+                    true
+                }
+                _ => false,
+            })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ASTBlockStatement {
     pub statements: Vec<ASTStatement>,
@@ -218,6 +371,39 @@ impl ASTStatement {
         }))
     }
 
+    pub fn match_statement(
+        match_keyword: TokenInfo,
+        scrutinee: ASTExpression,
+        arms: Vec<ASTMatchArm>,
+        else_branch: Option<ASTElseStatement>,
+    ) -> Self {
+        ASTStatement::new(ASTStatementKind::Match(ASTMatchStatement {
+            match_keyword,
+            scrutinee,
+            arms,
+            else_branch,
+        }))
+    }
+
+    pub fn struct_statement(
+        struct_keyword: TokenInfo,
+        identifier: TokenInfo,
+        fields: Vec<ASTStructField>,
+    ) -> Self {
+        ASTStatement::new(ASTStatementKind::Struct(ASTStructStatement {
+            struct_keyword,
+            identifier,
+            fields,
+        }))
+    }
+
+    pub fn namespace_statement(namespace_keyword: TokenInfo, path: Vec<TokenInfo>) -> Self {
+        ASTStatement::new(ASTStatementKind::Namespace(ASTNamespaceStatement {
+            namespace_keyword,
+            path,
+        }))
+    }
+
     pub fn block_statement(statements: Vec<ASTStatement>) -> Self {
         ASTStatement::new(ASTStatementKind::Block(ASTBlockStatement { statements }))
     }
@@ -246,12 +432,14 @@ impl ASTStatement {
 
     pub fn func_decl_statement(
         identifier: TokenInfo,
+        generics: Vec<TokenInfo>,
         parameters: Vec<FuncDeclParameter>,
         type_annotation: Option<ASTExpression>,
         body: ASTStatement,
     ) -> Self {
         ASTStatement::new(ASTStatementKind::FuncDecl(ASTFuncDeclStatement {
             identifier,
+            generics,
             parameters,
             type_annotation,
             body: Box::new(body),
@@ -276,6 +464,78 @@ pub enum ASTExpressionKind {
     Array(ASTArrayExpression),
     ArrayIndex(ASTArrayIndexExpression),
     TypeAnnotation(ASTTypeAnnotationExpression),
+    StructLiteral(ASTStructLiteralExpression),
+    FieldAccess(ASTFieldAccessExpression),
+    FieldAssignment(ASTFieldAssignmentExpression),
+    CompoundAssignment(ASTCompoundAssignmentExpression),
+    Closure(ASTClosureExpression),
+    FunctionType(ASTFunctionTypeExpression),
+}
+
+#[derive(Debug, Clone)]
+pub struct ASTStructLiteralField {
+    pub identifier: TokenInfo,
+    pub expression: ASTExpression,
+}
+
+#[derive(Debug, Clone)]
+pub struct ASTStructLiteralExpression {
+    pub identifier: TokenInfo,
+    pub fields: Vec<ASTStructLiteralField>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ASTFieldAccessExpression {
+    pub object: Box<ASTExpression>,
+    pub dot: TokenInfo,
+    pub field: TokenInfo,
+}
+
+#[derive(Debug, Clone)]
+pub struct ASTFieldAssignmentExpression {
+    pub field_access: ASTFieldAccessExpression,
+    pub expression: Box<ASTExpression>,
+}
+
+/// `target op= expression`, where the target is a variable, an array
+/// element or a field.
+#[derive(Debug, Clone)]
+pub struct ASTCompoundAssignmentExpression {
+    pub target: Box<ASTExpression>,
+    /// The operation applied to the target's value and `expression`. Its
+    /// token is the compound operator, such as `+=`.
+    pub operator: ASTBinaryOperator,
+    pub expression: Box<ASTExpression>,
+}
+
+/// An anonymous function, `fn (i: i32): bool { return i > 0 }`.
+#[derive(Debug, Clone)]
+pub struct ASTClosureExpression {
+    pub fn_keyword: TokenInfo,
+    pub parameters: Vec<FuncDeclParameter>,
+    pub type_annotation: Option<Box<ASTExpression>>,
+    pub body: Box<ASTStatement>,
+    /// The variables of enclosing functions the body uses. Filled in by the
+    /// capture analysis.
+    pub captures: RefCell<Vec<ASTCapture>>,
+}
+
+/// A variable an anonymous function uses from the function it is created
+/// in. `mut` variables are captured by reference so that assignments on
+/// either side are seen by the other, all others by copy.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ASTCapture {
+    pub identifier: String,
+    pub by_reference: bool,
+}
+
+/// The type of a function value, `fn(i32, String): bool`. Without a return
+/// type the function returns nothing.
+#[derive(Debug, Clone)]
+pub struct ASTFunctionTypeExpression {
+    pub fn_keyword: TokenInfo,
+    pub parameters: Vec<ASTExpression>,
+    pub return_type: Option<Box<ASTExpression>>,
 }
 
 #[derive(Debug, Clone)]
@@ -286,7 +546,11 @@ pub struct ASTRangeExpression {
 
 #[derive(Debug, Clone)]
 pub struct ASTCallExpression {
+    /// The namespaces before the function name in `A::B::func(x)`.
+    pub qualifier: Vec<TokenInfo>,
     pub identifier: TokenInfo,
+    /// The explicit type arguments of `func::<i32>(x)`.
+    pub generic_arguments: Vec<ASTExpression>,
     pub arguments: Vec<ASTExpression>,
 }
 #[derive(Debug, Clone)]
@@ -311,6 +575,7 @@ pub struct ASTArrayAssignmentExpression {
 pub enum ASTUnaryOperatorKind {
     Subtraction,
     BwNot,
+    Not,
 }
 #[derive(Debug, Clone)]
 pub struct ASTUnaryOperator {
@@ -351,7 +616,7 @@ impl ASTVariableExpression {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ASTBinaryOperatorKind {
     // Arithmetic
     Addition,
@@ -359,16 +624,24 @@ pub enum ASTBinaryOperatorKind {
 
     Star,
     Division,
+    Modulo,
     // Power,
     // Bitwise
     BwAnd,
     BwOr,
     BwXor,
+    BwShl,
+    BwShr,
     // Relational
     Equals,
     Inequal,
     LowerThan,
     GreaterThan,
+    LowerEquals,
+    GreaterEquals,
+    // Logical
+    And,
+    Or,
 }
 
 #[derive(Debug, Clone)]
@@ -381,19 +654,58 @@ impl ASTBinaryOperator {
         ASTBinaryOperator { kind, token }
     }
     pub fn precedence(&self) -> u8 {
-        match self.kind {
-            ASTBinaryOperatorKind::Star => 19,
-            ASTBinaryOperatorKind::Division => 19,
-            ASTBinaryOperatorKind::Addition => 18,
-            ASTBinaryOperatorKind::Subtraction => 18,
-            ASTBinaryOperatorKind::BwAnd => 17,
-            ASTBinaryOperatorKind::BwXor => 16,
-            ASTBinaryOperatorKind::BwOr => 15,
-            ASTBinaryOperatorKind::Equals => 30,
-            ASTBinaryOperatorKind::Inequal => 30,
-            ASTBinaryOperatorKind::LowerThan => 29,
-            ASTBinaryOperatorKind::GreaterThan => 29,
-        }
+        self.kind.precedence()
+    }
+}
+
+/// The precedence of `=`, which binds looser than every binary operator and
+/// groups to the right: `a = b = c` is `a = (b = c)`.
+pub const ASSIGNMENT_PRECEDENCE: u8 = 1;
+/// The precedence of prefix operators, which bind tighter than every
+/// binary operator.
+pub const UNARY_PRECEDENCE: u8 = ASSIGNMENT_PRECEDENCE + 1 + BINARY_PRECEDENCE_LEVELS.len() as u8;
+
+/// The binary operators grouped by precedence, from the loosest binding
+/// level to the tightest. The levels follow C++, so an expression groups the
+/// same way in the interpreter and in the emitted code. All of them are left
+/// associative.
+const BINARY_PRECEDENCE_LEVELS: [&[ASTBinaryOperatorKind]; 10] = [
+    &[ASTBinaryOperatorKind::Or],
+    &[ASTBinaryOperatorKind::And],
+    &[ASTBinaryOperatorKind::BwOr],
+    &[ASTBinaryOperatorKind::BwXor],
+    &[ASTBinaryOperatorKind::BwAnd],
+    &[
+        ASTBinaryOperatorKind::Equals,
+        ASTBinaryOperatorKind::Inequal,
+    ],
+    &[
+        ASTBinaryOperatorKind::LowerThan,
+        ASTBinaryOperatorKind::GreaterThan,
+        ASTBinaryOperatorKind::LowerEquals,
+        ASTBinaryOperatorKind::GreaterEquals,
+    ],
+    &[ASTBinaryOperatorKind::BwShl, ASTBinaryOperatorKind::BwShr],
+    &[
+        ASTBinaryOperatorKind::Addition,
+        ASTBinaryOperatorKind::Subtraction,
+    ],
+    &[
+        ASTBinaryOperatorKind::Star,
+        ASTBinaryOperatorKind::Division,
+        ASTBinaryOperatorKind::Modulo,
+    ],
+];
+
+impl ASTBinaryOperatorKind {
+    /// Higher binds tighter. Every binary operator lies strictly between
+    /// `ASSIGNMENT_PRECEDENCE` and `UNARY_PRECEDENCE`.
+    pub fn precedence(&self) -> u8 {
+        let level = BINARY_PRECEDENCE_LEVELS
+            .iter()
+            .position(|level| level.contains(self))
+            .unwrap();
+        ASSIGNMENT_PRECEDENCE + 1 + level as u8
     }
 }
 
@@ -407,11 +719,33 @@ pub struct ASTBinaryExpression {
 #[derive(Debug, Clone)]
 pub struct ASTNumberExpression {
     pub num: TokenInfo,
+    pub value: NumberValue,
+    /// The type written as a suffix, as in `10u8` or `2.0f32`.
+    pub suffix: Option<Type>,
+}
+
+impl ASTNumberExpression {
+    /// The type of the literal: its suffix, or `i32` and `f64` without one.
+    pub fn kind(&self) -> Type {
+        match (&self.suffix, self.value) {
+            (Some(suffix), _) => suffix.clone(),
+            (None, NumberValue::Int(_)) => Type::Int(Int::I32),
+            (None, NumberValue::Float(_)) => Type::Float(Float::F64),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct ASTStringExpression {
     pub token: TokenInfo,
+    /// The literal's text with its quotes removed and escapes decoded.
+    pub value: String,
+}
+
+impl ASTStringExpression {
+    pub fn is_char(&self) -> bool {
+        self.token.token == Token::Char
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -422,20 +756,165 @@ pub struct ASTParenthesizedExpression {
 #[derive(Debug, Clone)]
 pub struct ASTExpression {
     pub kind: ASTExpressionKind,
+    /// Filled in by the type checker.
+    pub ty: RefCell<Option<Type>>,
 }
 
 impl ASTExpression {
     pub fn new(kind: ASTExpressionKind) -> Self {
-        ASTExpression { kind }
+        ASTExpression {
+            kind,
+            ty: RefCell::new(None),
+        }
     }
 
-    pub fn range(start: Box<ASTExpression>, end: Box<ASTExpression>) -> Self {
-        Self {
-            kind: ASTExpressionKind::Range(ASTRangeExpression { start, end }),
+    pub fn ty(&self) -> Option<Type> {
+        self.ty.borrow().clone()
+    }
+
+    pub fn set_ty(&self, ty: Type) {
+        *self.ty.borrow_mut() = Some(ty);
+    }
+
+    /// The number literal this expression consists of, possibly behind a
+    /// unary operator such as a minus sign.
+    pub fn number_literal(&self) -> Option<&ASTNumberExpression> {
+        match &self.kind {
+            ASTExpressionKind::Number(number) => Some(number),
+            ASTExpressionKind::Unary(unary) => match &unary.operand.kind {
+                ASTExpressionKind::Number(number) => Some(number),
+                _ => None,
+            },
+            _ => None,
         }
     }
+
+    pub fn is_integer_literal(&self) -> bool {
+        self.number_literal()
+            .is_some_and(|number| matches!(number.value, NumberValue::Int(_)))
+    }
+
+    /// The value of an integer literal, negated or complemented by the unary
+    /// operator in front of it.
+    pub fn integer_literal_value(&self) -> Option<i128> {
+        let value = match self.number_literal()?.value {
+            NumberValue::Int(value) => value as i128,
+            NumberValue::Float(_) => return None,
+        };
+        match &self.kind {
+            ASTExpressionKind::Unary(unary) => match unary.operator.kind {
+                ASTUnaryOperatorKind::Subtraction => Some(-value),
+                ASTUnaryOperatorKind::BwNot => Some(!value),
+                ASTUnaryOperatorKind::Not => None,
+            },
+            _ => Some(value),
+        }
+    }
+
+    /// Whether this expression denotes something that can be assigned to: a
+    /// variable, or an element or field of one.
+    pub fn is_place(&self) -> bool {
+        match &self.kind {
+            ASTExpressionKind::Variable(_) => true,
+            ASTExpressionKind::ArrayIndex(expr) => expr.array.is_place(),
+            ASTExpressionKind::FieldAccess(expr) => expr.object.is_place(),
+            _ => false,
+        }
+    }
+
+    /// How tightly the outermost operator of this expression binds. Operands
+    /// of a looser operator need parentheses to keep their grouping.
+    pub fn precedence(&self) -> u8 {
+        match &self.kind {
+            ASTExpressionKind::Binary(expr) => expr.operator.precedence(),
+            ASTExpressionKind::Unary(_) => UNARY_PRECEDENCE,
+            ASTExpressionKind::Assignment(_)
+            | ASTExpressionKind::ArrayAssignment(_)
+            | ASTExpressionKind::FieldAssignment(_)
+            | ASTExpressionKind::CompoundAssignment(_) => ASSIGNMENT_PRECEDENCE,
+            _ => u8::MAX,
+        }
+    }
+
+    pub fn first_token(&self) -> Option<&TokenInfo> {
+        match &self.kind {
+            ASTExpressionKind::Number(expr) => Some(&expr.num),
+            ASTExpressionKind::String(expr) => Some(&expr.token),
+            ASTExpressionKind::Binary(expr) => expr.left.first_token(),
+            ASTExpressionKind::Unary(expr) => Some(&expr.operator.token),
+            ASTExpressionKind::Parenthesized(expr) => expr.expression.first_token(),
+            ASTExpressionKind::Variable(expr) => Some(&expr.identifier),
+            ASTExpressionKind::Assignment(expr) => Some(&expr.identifier),
+            ASTExpressionKind::ArrayAssignment(expr) => Some(&expr.identifier),
+            ASTExpressionKind::Boolean(expr) => Some(&expr.token),
+            ASTExpressionKind::Call(expr) => expr.qualifier.first().or(Some(&expr.identifier)),
+            ASTExpressionKind::StdCall(expr) => Some(&expr.std_keyword),
+            ASTExpressionKind::Range(expr) => expr.start.first_token(),
+            ASTExpressionKind::Array(expr) => expr.elements.first()?.first_token(),
+            ASTExpressionKind::ArrayIndex(expr) => expr.array.first_token(),
+            ASTExpressionKind::TypeAnnotation(expr) => Some(&expr.base),
+            ASTExpressionKind::StructLiteral(expr) => Some(&expr.identifier),
+            ASTExpressionKind::FieldAccess(expr) => expr.object.first_token(),
+            ASTExpressionKind::FieldAssignment(expr) => expr.field_access.object.first_token(),
+            ASTExpressionKind::CompoundAssignment(expr) => expr.target.first_token(),
+            ASTExpressionKind::Closure(expr) => Some(&expr.fn_keyword),
+            ASTExpressionKind::FunctionType(expr) => Some(&expr.fn_keyword),
+        }
+    }
+
+    pub fn last_token(&self) -> Option<&TokenInfo> {
+        match &self.kind {
+            ASTExpressionKind::Binary(expr) => expr.right.last_token(),
+            ASTExpressionKind::Unary(expr) => expr.operand.last_token(),
+            ASTExpressionKind::Parenthesized(expr) => expr.expression.last_token(),
+            ASTExpressionKind::Assignment(expr) => expr.expression.last_token(),
+            ASTExpressionKind::ArrayAssignment(expr) => expr.expression.last_token(),
+            ASTExpressionKind::Call(expr) => match expr.arguments.last() {
+                Some(argument) => argument.last_token(),
+                None => Some(&expr.identifier),
+            },
+            ASTExpressionKind::StdCall(expr) => match expr.arguments.last() {
+                Some(argument) => argument.last_token(),
+                None => Some(&expr.identifier),
+            },
+            ASTExpressionKind::Range(expr) => expr.end.last_token(),
+            ASTExpressionKind::Array(expr) => expr.elements.last()?.last_token(),
+            ASTExpressionKind::ArrayIndex(expr) => expr.index.last_token(),
+            ASTExpressionKind::TypeAnnotation(expr) => match expr.generics.last() {
+                Some(generic) => generic.last_token(),
+                None => Some(&expr.base),
+            },
+            ASTExpressionKind::StructLiteral(expr) => match expr.fields.last() {
+                Some(field) => field.expression.last_token(),
+                None => Some(&expr.identifier),
+            },
+            ASTExpressionKind::FieldAccess(expr) => Some(&expr.field),
+            ASTExpressionKind::FieldAssignment(expr) => expr.expression.last_token(),
+            ASTExpressionKind::CompoundAssignment(expr) => expr.expression.last_token(),
+            ASTExpressionKind::FunctionType(expr) => match &expr.return_type {
+                Some(return_type) => return_type.last_token(),
+                None => expr
+                    .parameters
+                    .last()
+                    .map_or(Some(&expr.fn_keyword), |parameter| parameter.last_token()),
+            },
+            _ => self.first_token(),
+        }
+    }
+
+    pub fn range(start: Box<ASTExpression>, end: Box<ASTExpression>) -> Self {
+        ASTExpression::new(ASTExpressionKind::Range(ASTRangeExpression { start, end }))
+    }
     pub fn number(num: TokenInfo) -> Self {
-        ASTExpression::new(ASTExpressionKind::Number(ASTNumberExpression { num }))
+        let (value, suffix) = match tokenizer::number_literal(&num.lexeme) {
+            Some((value, suffix)) => (value, suffix.and_then(Type::from_name)),
+            None => (NumberValue::Int(0), None),
+        };
+        ASTExpression::new(ASTExpressionKind::Number(ASTNumberExpression {
+            num,
+            value,
+            suffix,
+        }))
     }
     pub fn binary(operator: ASTBinaryOperator, left: ASTExpression, right: ASTExpression) -> Self {
         ASTExpression::new(ASTExpressionKind::Binary(ASTBinaryExpression {
@@ -492,12 +971,27 @@ impl ASTExpression {
     }
 
     pub fn string(token: TokenInfo) -> Self {
-        ASTExpression::new(ASTExpressionKind::String(ASTStringExpression { token }))
+        let value = tokenizer::literal_value(&token.lexeme);
+        ASTExpression::new(ASTExpressionKind::String(ASTStringExpression {
+            token,
+            value,
+        }))
     }
 
     pub fn call(identifier: TokenInfo, arguments: Vec<ASTExpression>) -> Self {
+        Self::qualified_call(Vec::new(), identifier, Vec::new(), arguments)
+    }
+
+    pub fn qualified_call(
+        qualifier: Vec<TokenInfo>,
+        identifier: TokenInfo,
+        generic_arguments: Vec<ASTExpression>,
+        arguments: Vec<ASTExpression>,
+    ) -> Self {
         ASTExpression::new(ASTExpressionKind::Call(ASTCallExpression {
+            qualifier,
             identifier,
+            generic_arguments,
             arguments,
         }))
     }
@@ -517,20 +1011,98 @@ impl ASTExpression {
     }
 
     pub fn array(elements: Vec<ASTExpression>) -> Self {
-        ASTExpression {
-            kind: ASTExpressionKind::Array(ASTArrayExpression { elements }),
-        }
+        ASTExpression::new(ASTExpressionKind::Array(ASTArrayExpression { elements }))
     }
 
     pub fn array_index(array: Box<ASTExpression>, index: Box<ASTExpression>) -> Self {
-        ASTExpression {
-            kind: ASTExpressionKind::ArrayIndex(ASTArrayIndexExpression { array, index }),
-        }
+        ASTExpression::new(ASTExpressionKind::ArrayIndex(ASTArrayIndexExpression {
+            array,
+            index,
+        }))
     }
 
-    pub fn type_annotation(base: TokenInfo, generics: Vec<TokenInfo>) -> Self {
-        ASTExpression {
-            kind: ASTExpressionKind::TypeAnnotation(ASTTypeAnnotationExpression { base, generics }),
-        }
+    pub fn type_annotation(base: TokenInfo, generics: Vec<ASTExpression>) -> Self {
+        Self::qualified_type_annotation(Vec::new(), base, generics)
+    }
+
+    pub fn qualified_type_annotation(
+        qualifier: Vec<TokenInfo>,
+        base: TokenInfo,
+        generics: Vec<ASTExpression>,
+    ) -> Self {
+        ASTExpression::new(ASTExpressionKind::TypeAnnotation(
+            ASTTypeAnnotationExpression {
+                qualifier,
+                base,
+                generics,
+            },
+        ))
+    }
+
+    pub fn struct_literal(identifier: TokenInfo, fields: Vec<ASTStructLiteralField>) -> Self {
+        ASTExpression::new(ASTExpressionKind::StructLiteral(
+            ASTStructLiteralExpression { identifier, fields },
+        ))
+    }
+
+    pub fn closure(
+        fn_keyword: TokenInfo,
+        parameters: Vec<FuncDeclParameter>,
+        type_annotation: Option<ASTExpression>,
+        body: ASTStatement,
+    ) -> Self {
+        ASTExpression::new(ASTExpressionKind::Closure(ASTClosureExpression {
+            fn_keyword,
+            parameters,
+            type_annotation: type_annotation.map(Box::new),
+            body: Box::new(body),
+            captures: RefCell::new(Vec::new()),
+        }))
+    }
+
+    pub fn function_type(
+        fn_keyword: TokenInfo,
+        parameters: Vec<ASTExpression>,
+        return_type: Option<ASTExpression>,
+    ) -> Self {
+        ASTExpression::new(ASTExpressionKind::FunctionType(ASTFunctionTypeExpression {
+            fn_keyword,
+            parameters,
+            return_type: return_type.map(Box::new),
+        }))
+    }
+
+    pub fn field_access(object: ASTExpression, dot: TokenInfo, field: TokenInfo) -> Self {
+        ASTExpression::new(ASTExpressionKind::FieldAccess(ASTFieldAccessExpression {
+            object: Box::new(object),
+            dot,
+            field,
+        }))
+    }
+
+    pub fn field_assignment(
+        field_access: ASTFieldAccessExpression,
+        expression: ASTExpression,
+    ) -> Self {
+        ASTExpression::new(ASTExpressionKind::FieldAssignment(
+            ASTFieldAssignmentExpression {
+                field_access,
+                expression: Box::new(expression),
+            },
+        ))
+    }
+
+    pub fn compound_assignment(
+        target: ASTExpression,
+        operator: ASTBinaryOperator,
+        expression: ASTExpression,
+    ) -> Self {
+        ASTExpression::new(ASTExpressionKind::CompoundAssignment(
+            ASTCompoundAssignmentExpression {
+                target: Box::new(target),
+                operator,
+                expression: Box::new(expression),
+            },
+        ))
     }
 }
@@ -1,11 +1,14 @@
+use crate::diagnostics::{Diagnostic, Span};
 use crate::tokenizer::{Token, TokenInfo};
 
 use crate::parser::{
-    ASTBinaryOperator,
-    ASTBinaryOperatorKind, ASTElseStatement, ASTExpression, ASTStatement, ASTUnaryOperator, ASTUnaryOperatorKind,
-    FuncDeclParameter,
+    ASTBinaryOperator, ASTBinaryOperatorKind, ASTElseStatement, ASTExpression, ASTExpressionKind,
+    ASTMatchArm, ASTMatchPattern, ASTRangeExpression, ASTStatement, ASTStructField,
+    ASTStructLiteralField, ASTUnaryOperator, ASTUnaryOperatorKind, FuncDeclParameter, ParseError,
+    ASSIGNMENT_PRECEDENCE,
 };
 use std::cell::Cell;
+use std::collections::HashSet;
 
 pub struct Counter {
     value: Cell<usize>,
@@ -29,6 +32,12 @@ impl Counter {
 pub struct Node {
     tokens: Vec<TokenInfo>,
     current: Counter,
+    struct_names: HashSet<String>,
+    errors: Vec<ParseError>,
+    warnings: Vec<Diagnostic>,
+    // Set while parsing inside `()`, `[]` or a struct literal, where line
+    // breaks never end an expression.
+    inside_brackets: bool,
 }
 
 impl Node {
@@ -36,21 +45,76 @@ impl Node {
         Self {
             tokens: tokens.iter().map(|token| token.clone()).collect(),
             current: Counter::new(),
+            struct_names: HashSet::new(),
+            errors: Vec::new(),
+            warnings: Vec::new(),
+            inside_brackets: false,
         }
     }
 
     pub fn next_statement(&mut self) -> Option<ASTStatement> {
-        if self.is_at_end() {
-            return None;
+        loop {
+            if self.is_at_end() {
+                return None;
+            }
+            let start = self.current.get_value();
+            match self.parse_statement() {
+                Ok(statement) => return Some(statement),
+                Err(error) => self.recover(error, start),
+            }
         }
-        Some(self.parse_statement())
     }
 
     pub fn is_at_end(&self) -> bool {
         self.current().token == Token::EOF
     }
 
-    pub fn parse_statement(&mut self) -> ASTStatement {
+    pub fn take_errors(&mut self) -> Vec<ParseError> {
+        std::mem::take(&mut self.errors)
+    }
+
+    pub fn take_warnings(&mut self) -> Vec<Diagnostic> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    fn recover(&mut self, error: ParseError, start: usize) {
+        if self.current.get_value() == start {
+            self.consume();
+        }
+        self.synchronize(&error);
+        self.errors.push(error);
+    }
+
+    fn synchronize(&mut self, error: &ParseError) {
+        let row = error.found.start_position.row;
+        while !self.is_at_end() {
+            let token = self.current();
+            if token.start_position.row > row
+                || token.token == Token::RightBraces
+                || Self::is_statement_start(token.token)
+            {
+                return;
+            }
+            self.consume();
+        }
+    }
+
+    fn is_statement_start(token: Token) -> bool {
+        matches!(
+            token,
+            Token::Let
+                | Token::If
+                | Token::While
+                | Token::Fn
+                | Token::For
+                | Token::Return
+                | Token::Match
+                | Token::Struct
+                | Token::Namespace
+        )
+    }
+
+    pub fn parse_statement(&mut self) -> Result<ASTStatement, ParseError> {
         match self.current().token {
             Token::Let => self.parse_let_statement(),
             Token::If => self.parse_if_statement(),
@@ -59,235 +123,576 @@ impl Node {
             Token::Fn => self.parse_function_declaration(),
             Token::For => self.parse_for_statement(),
             Token::Return => self.parse_return_statement(),
+            Token::Match => self.parse_match_statement(),
+            Token::Struct => self.parse_struct_statement(),
+            Token::Namespace => self.parse_namespace_statement(),
             _ => self.parse_expression_statement(),
         }
     }
 
-    fn parse_return_statement(&mut self) -> ASTStatement {
-        let keyword = self.consume_and_check(Token::Return).clone();
-        let value = self.parse_expression();
-        ASTStatement::return_statement(keyword, Some(value))
+    fn parse_return_statement(&mut self) -> Result<ASTStatement, ParseError> {
+        let keyword = self.consume_and_check(Token::Return)?.clone();
+        let value = self.parse_expression()?;
+        self.parse_statement_end()?;
+        Ok(ASTStatement::return_statement(keyword, Some(value)))
     }
 
-    fn parse_function_declaration(&mut self) -> ASTStatement {
-        self.consume_and_check(Token::Fn);
-        let identifier = self.consume_and_check(Token::Identifier).clone();
-        let parameters = self.parse_optional_parameter_list();
+    fn parse_function_declaration(&mut self) -> Result<ASTStatement, ParseError> {
+        self.consume_and_check(Token::Fn)?;
+        let identifier = self.consume_and_check(Token::Identifier)?.clone();
+        let generics = self.parse_optional_generic_parameters()?;
+        let parameters = self.parse_optional_parameter_list()?;
         let return_type = if self.peek(0).token == Token::Colon {
-            self.consume_and_check(Token::Colon);
-            self.parse_type_annotation()
+            self.consume_and_check(Token::Colon)?;
+            self.parse_type_annotation()?
         } else {
             None
         };
-        let body = self.parse_statement();
-        ASTStatement::func_decl_statement(identifier, parameters, return_type, body)
+        let body = self.parse_statement()?;
+        Ok(ASTStatement::func_decl_statement(
+            identifier,
+            generics,
+            parameters,
+            return_type,
+            body,
+        ))
+    }
+
+    fn parse_optional_generic_parameters(&mut self) -> Result<Vec<TokenInfo>, ParseError> {
+        let mut generics = Vec::new();
+        if self.current().token != Token::LowerThan {
+            return Ok(generics);
+        }
+        self.consume_and_check(Token::LowerThan)?;
+        while self.current().token != Token::GreaterThan && !self.is_at_end() {
+            generics.push(self.consume_and_check(Token::Identifier)?.clone());
+            if self.current().token != Token::GreaterThan {
+                self.consume_and_check_one_of(&[Token::Comma, Token::GreaterThan])?;
+            }
+        }
+        self.consume_and_check(Token::GreaterThan)?;
+        Ok(generics)
     }
 
-    fn parse_optional_parameter_list(&mut self) -> Vec<FuncDeclParameter> {
+    /// Parses the `::<i32, T>` of a call that names the type arguments of a
+    /// generic function explicitly.
+    fn parse_optional_generic_arguments(&mut self) -> Result<Vec<ASTExpression>, ParseError> {
+        if self.current().token != Token::DoubleColon {
+            return Ok(Vec::new());
+        }
+        self.consume_and_check(Token::DoubleColon)?;
+        self.parse_type_arguments()
+    }
+
+    /// Parses the type arguments between `<` and `>`, as in `Array<i32>`.
+    fn parse_type_arguments(&mut self) -> Result<Vec<ASTExpression>, ParseError> {
+        self.consume_and_check(Token::LowerThan)?;
+        let mut arguments = Vec::new();
+        while !self.at_closing_angle() && !self.is_at_end() {
+            arguments.extend(self.parse_type_annotation()?);
+            if !self.at_closing_angle() {
+                self.consume_and_check_one_of(&[Token::Comma, Token::GreaterThan])?;
+            }
+        }
+        self.consume_closing_angle()?;
+        Ok(arguments)
+    }
+
+    fn at_closing_angle(&self) -> bool {
+        matches!(self.current().token, Token::GreaterThan | Token::BwShr)
+    }
+
+    /// Consumes the `>` closing a list of type arguments. The `>>` closing
+    /// two nested lists is one token, so only its first half is consumed and
+    /// the second is left in its place.
+    fn consume_closing_angle(&mut self) -> Result<(), ParseError> {
+        if self.current().token != Token::BwShr {
+            self.consume_and_check(Token::GreaterThan)?;
+            return Ok(());
+        }
+        let token = &mut self.tokens[self.current.get_value()];
+        token.token = Token::GreaterThan;
+        token.lexeme = String::from(">");
+        token.start_position.col += 1;
+        token.start_position.offset += 1;
+        token.leading_trivia.clear();
+        Ok(())
+    }
+
+    fn parse_optional_parameter_list(&mut self) -> Result<Vec<FuncDeclParameter>, ParseError> {
         if self.current().token != Token::LeftParantheses {
-            return Vec::new();
+            return Ok(Vec::new());
         }
-        self.consume_and_check(Token::LeftParantheses);
+        self.consume_and_check(Token::LeftParantheses)?;
         let mut parameters = Vec::new();
         while self.current().token != Token::RightParantheses && !self.is_at_end() {
-            let identifier = self.consume_and_check(Token::Identifier).clone();
+            let is_mut = if self.current().token == Token::Mut {
+                self.consume_and_check(Token::Mut)?;
+                true
+            } else {
+                false
+            };
+            let identifier = self.consume_and_check(Token::Identifier)?.clone();
             let type_annotation = if self.peek(0).token == Token::Colon {
-                self.consume_and_check(Token::Colon);
-                self.parse_type_annotation()
+                self.consume_and_check(Token::Colon)?;
+                self.parse_type_annotation()?
             } else {
                 None
             };
             parameters.push(FuncDeclParameter {
+                is_mut,
                 identifier,
                 type_annotation,
             });
             if self.current().token == Token::Comma {
-                self.consume_and_check(Token::Comma);
+                self.consume_and_check(Token::Comma)?;
             }
         }
-        self.consume_and_check(Token::RightParantheses);
-        parameters
-    }
-    fn parse_while_statement(&mut self) -> ASTStatement {
-        let while_keyword = self.consume_and_check(Token::While).clone();
-        let condition_expr = self.parse_expression();
-        let body = self.parse_statement();
-        ASTStatement::while_statement(while_keyword, condition_expr, body)
-    }
-
-    fn parse_for_statement(&mut self) -> ASTStatement {
-        let for_keyword = self.consume_and_check(Token::For).clone();
-        let identifier = self.consume_and_check(Token::Identifier).clone();
+        self.consume_and_check(Token::RightParantheses)?;
+        Ok(parameters)
+    }
+    fn parse_while_statement(&mut self) -> Result<ASTStatement, ParseError> {
+        let while_keyword = self.consume_and_check(Token::While)?.clone();
+        let condition_expr = self.parse_expression()?;
+        let body = self.parse_statement()?;
+        Ok(ASTStatement::while_statement(
+            while_keyword,
+            condition_expr,
+            body,
+        ))
+    }
+
+    fn parse_for_statement(&mut self) -> Result<ASTStatement, ParseError> {
+        let for_keyword = self.consume_and_check(Token::For)?.clone();
+        let identifier = self.consume_and_check(Token::Identifier)?.clone();
         let type_annotation = if self.peek(0).token == Token::Colon {
-            self.consume_and_check(Token::Colon);
-            self.parse_type_annotation()
+            self.consume_and_check(Token::Colon)?;
+            self.parse_type_annotation()?
         } else {
             None
         };
 
-        self.consume_and_check(Token::In);
-
-        let iterable = self.parse_expression();
-
-        let body = self.parse_statement();
-        ASTStatement::for_statement(for_keyword, identifier, type_annotation, iterable, body)
+        self.consume_and_check(Token::In)?;
+
+        let iterable = self.parse_expression()?;
+
+        let body = self.parse_statement()?;
+        Ok(ASTStatement::for_statement(
+            for_keyword,
+            identifier,
+            type_annotation,
+            iterable,
+            body,
+        ))
+    }
+
+    fn parse_block_statement(&mut self) -> Result<ASTStatement, ParseError> {
+        self.consume_and_check(Token::LeftBraces)?;
+        let statements = self.with_brackets(false, |parser| {
+            let mut statements = Vec::new();
+            while parser.current().token != Token::RightBraces && !parser.is_at_end() {
+                let start = parser.current.get_value();
+                match parser.parse_statement() {
+                    Ok(statement) => statements.push(statement),
+                    Err(error) => parser.recover(error, start),
+                }
+            }
+            statements
+        });
+        self.consume_and_check(Token::RightBraces)?;
+        Ok(ASTStatement::block_statement(statements))
+    }
+
+    fn parse_if_statement(&mut self) -> Result<ASTStatement, ParseError> {
+        let if_keyword = self.consume_and_check(Token::If)?.clone();
+        let condition_expr = self.parse_expression()?;
+        let then = self.parse_statement()?;
+        let else_statement = self.parse_optional_else_statement()?;
+        Ok(ASTStatement::if_statement(
+            if_keyword,
+            condition_expr,
+            then,
+            else_statement,
+        ))
+    }
+
+    fn parse_struct_statement(&mut self) -> Result<ASTStatement, ParseError> {
+        let struct_keyword = self.consume_and_check(Token::Struct)?.clone();
+        let identifier = self.consume_and_check(Token::Identifier)?.clone();
+        self.struct_names.insert(identifier.lexeme.clone());
+        self.consume_and_check(Token::LeftBraces)?;
+        let mut fields = Vec::new();
+        while self.current().token != Token::RightBraces && !self.is_at_end() {
+            let field = self.consume_and_check(Token::Identifier)?.clone();
+            self.consume_and_check(Token::Colon)?;
+            let type_annotation = self.parse_type_annotation()?.unwrap();
+            fields.push(ASTStructField {
+                identifier: field,
+                type_annotation,
+            });
+            if self.current().token == Token::Comma {
+                self.consume_and_check(Token::Comma)?;
+            }
+        }
+        self.consume_and_check(Token::RightBraces)?;
+        Ok(ASTStatement::struct_statement(
+            struct_keyword,
+            identifier,
+            fields,
+        ))
+    }
+
+    fn parse_namespace_statement(&mut self) -> Result<ASTStatement, ParseError> {
+        let namespace_keyword = self.consume_and_check(Token::Namespace)?.clone();
+        let mut path = vec![self.consume_and_check(Token::Identifier)?.clone()];
+        while self.current().token == Token::DoubleColon {
+            self.consume_and_check(Token::DoubleColon)?;
+            path.push(self.consume_and_check(Token::Identifier)?.clone());
+        }
+        self.parse_statement_end()?;
+        Ok(ASTStatement::namespace_statement(namespace_keyword, path))
+    }
+
+    /// Parses the rest of a path like `A::B::name` after its first segment
+    /// and splits it into the qualifying namespaces and the name.
+    fn parse_path(&mut self, first: TokenInfo) -> Result<(Vec<TokenInfo>, TokenInfo), ParseError> {
+        let mut qualifier = Vec::new();
+        let mut name = first;
+        while self.current().token == Token::DoubleColon && self.peek(1).token != Token::LowerThan {
+            self.consume_and_check(Token::DoubleColon)?;
+            let segment = self.consume_and_check(Token::Identifier)?.clone();
+            qualifier.push(std::mem::replace(&mut name, segment));
+        }
+        Ok((qualifier, name))
     }
 
-    fn parse_block_statement(&mut self) -> ASTStatement {
-        self.consume_and_check(Token::LeftBraces);
-        let mut statements = Vec::new();
+    fn parse_match_statement(&mut self) -> Result<ASTStatement, ParseError> {
+        let match_keyword = self.consume_and_check(Token::Match)?.clone();
+        let scrutinee = self.parse_expression()?;
+        self.consume_and_check(Token::LeftBraces)?;
+        let mut arms = Vec::new();
         while self.current().token != Token::RightBraces && !self.is_at_end() {
-            statements.push(self.parse_statement());
+            let pattern = self.parse_match_pattern()?;
+            let arrow = self.consume_and_check(Token::Arrow)?.clone();
+            let body = self.parse_statement()?;
+            arms.push(ASTMatchArm {
+                pattern,
+                arrow,
+                body: Box::new(body),
+            });
+        }
+        self.consume_and_check(Token::RightBraces)?;
+        let else_statement = self.parse_optional_else_statement()?;
+        Ok(ASTStatement::match_statement(
+            match_keyword,
+            scrutinee,
+            arms,
+            else_statement,
+        ))
+    }
+
+    fn parse_match_pattern(&mut self) -> Result<ASTMatchPattern, ParseError> {
+        if self.current().token == Token::Identifier {
+            let identifier = self.consume_and_check(Token::Identifier)?.clone();
+            if identifier.lexeme == "_" {
+                return Ok(ASTMatchPattern::Wildcard(identifier));
+            }
+            return Ok(ASTMatchPattern::Binding(identifier));
         }
-        self.consume_and_check(Token::RightBraces);
-        ASTStatement::block_statement(statements)
+
+        let start = self.parse_match_literal()?;
+        if self.current().token == Token::Range {
+            self.consume_and_check(Token::Range)?;
+            let end = self.parse_match_literal()?;
+            return Ok(ASTMatchPattern::Range(ASTRangeExpression {
+                start: Box::new(start),
+                end: Box::new(end),
+            }));
+        }
+        Ok(ASTMatchPattern::Literal(Box::new(start)))
     }
 
-    fn parse_if_statement(&mut self) -> ASTStatement {
-        let if_keyword = self.consume_and_check(Token::If).clone();
-        let condition_expr = self.parse_expression();
-        let then = self.parse_statement();
-        let else_statement = self.parse_optional_else_statement();
-        ASTStatement::if_statement(if_keyword, condition_expr, then, else_statement)
+    fn parse_match_literal(&mut self) -> Result<ASTExpression, ParseError> {
+        if let Some(operator) = self.parse_unary_operator() {
+            self.consume();
+            let operand = self.consume_and_check(Token::Number)?.clone();
+            return Ok(ASTExpression::unary(
+                operator,
+                ASTExpression::number(operand),
+            ));
+        }
+        match self.current().token {
+            Token::Number => Ok(ASTExpression::number(self.consume().clone())),
+            Token::String | Token::Char => Ok(ASTExpression::string(self.consume().clone())),
+            Token::True | Token::False => self.parse_primary_expression(),
+            _ => Err(self.error(&[
+                Token::Identifier,
+                Token::Number,
+                Token::String,
+                Token::Char,
+                Token::True,
+                Token::False,
+                Token::Subtraction,
+            ])),
+        }
     }
 
-    fn parse_optional_else_statement(&mut self) -> Option<ASTElseStatement> {
+    fn parse_optional_else_statement(&mut self) -> Result<Option<ASTElseStatement>, ParseError> {
         if self.current().token == Token::Else {
-            let else_keyword = self.consume_and_check(Token::Else).clone();
-            let else_statement = self.parse_statement();
-            return Some(ASTElseStatement::new(else_keyword, else_statement));
+            let else_keyword = self.consume_and_check(Token::Else)?.clone();
+            let else_statement = self.parse_statement()?;
+            return Ok(Some(ASTElseStatement::new(else_keyword, else_statement)));
         }
-        return None;
+        return Ok(None);
     }
 
-    pub fn parse_type_annotation(&mut self) -> Option<ASTExpression> {
-        let base = self.consume_and_check(Token::Identifier).clone();
-        let mut generics = Vec::new();
+    pub fn parse_type_annotation(&mut self) -> Result<Option<ASTExpression>, ParseError> {
+        if self.current().token == Token::Fn {
+            return self.parse_function_type().map(Some);
+        }
+        let first = self.consume_and_check(Token::Identifier)?.clone();
+        let (qualifier, base) = self.parse_path(first)?;
+        let generics = if self.current().token == Token::LowerThan {
+            self.parse_type_arguments()?
+        } else {
+            Vec::new()
+        };
+
+        return Ok(Some(ASTExpression::qualified_type_annotation(
+            qualifier, base, generics,
+        )));
+    }
 
-        if self.current().token == Token::LowerThan {
-            self.consume_and_check(Token::LowerThan);
-            while self.current().token != Token::GreaterThan {
-                let generic = self.consume_and_check(Token::Identifier).clone();
-                generics.push(generic);
+    /// Parses the type of a function value, `fn(i32, String): bool`.
+    fn parse_function_type(&mut self) -> Result<ASTExpression, ParseError> {
+        let fn_keyword = self.consume_and_check(Token::Fn)?.clone();
+        self.consume_and_check(Token::LeftParantheses)?;
+        let mut parameters = Vec::new();
+        while self.current().token != Token::RightParantheses && !self.is_at_end() {
+            parameters.extend(self.parse_type_annotation()?);
+            if self.current().token != Token::RightParantheses {
+                self.consume_and_check_one_of(&[Token::Comma, Token::RightParantheses])?;
             }
-            self.consume_and_check(Token::GreaterThan);
         }
-
-        return Some(ASTExpression::type_annotation(base, generics));
+        self.consume_and_check(Token::RightParantheses)?;
+        let return_type = if self.current().token == Token::Colon {
+            self.consume_and_check(Token::Colon)?;
+            self.parse_type_annotation()?
+        } else {
+            None
+        };
+        Ok(ASTExpression::function_type(
+            fn_keyword,
+            parameters,
+            return_type,
+        ))
     }
 
-    fn parse_let_statement(&mut self) -> ASTStatement {
-        self.consume_and_check(Token::Let);
+    fn parse_let_statement(&mut self) -> Result<ASTStatement, ParseError> {
+        self.consume_and_check(Token::Let)?;
 
         let is_mut = if self.current().token == Token::Mut {
-            self.consume_and_check(Token::Mut);
+            self.consume_and_check(Token::Mut)?;
             true
         } else {
             false
         };
 
-        let identifier = self.consume_and_check(Token::Identifier).clone();
+        let identifier = self.consume_and_check(Token::Identifier)?.clone();
 
         let type_annotation = if self.peek(0).token == Token::Colon {
-            self.consume_and_check(Token::Colon);
-            self.parse_type_annotation()
+            self.consume_and_check(Token::Colon)?;
+            self.parse_type_annotation()?
         } else {
             None
         };
 
-        self.consume_and_check(Token::Assignment);
+        self.consume_and_check(Token::Assignment)?;
 
-        let expr = self.parse_expression();
+        let expr = self.parse_expression()?;
+        self.parse_statement_end()?;
 
-        return ASTStatement::let_statement(identifier, type_annotation, is_mut, expr);
+        return Ok(ASTStatement::let_statement(
+            identifier,
+            type_annotation,
+            is_mut,
+            expr,
+        ));
     }
 
-    fn parse_expression_statement(&mut self) -> ASTStatement {
-        let expr = self.parse_expression();
-        return ASTStatement::expression(expr);
+    fn parse_expression_statement(&mut self) -> Result<ASTStatement, ParseError> {
+        let expr = self.parse_expression()?;
+        self.parse_statement_end()?;
+        return Ok(ASTStatement::expression(expr));
     }
 
-    fn parse_expression(&mut self) -> ASTExpression {
-        if let Some(range_expr) = self.parse_range_expression_if_exists() {
-            return range_expr;
-        }
-        if self.current().token == Token::Std {
-            let keyword = self.current().clone();
-            self.consume_and_check(Token::Std);
-            let double_colon = self.current().clone();
-            self.consume_and_check(Token::DoubleColon);
-            let identifier = self.current().clone();
-            self.consume_and_check(Token::Identifier);
-
-            return self.parse_std_call_expression(keyword, double_colon, identifier);
+    /// A `let`, `return` or expression statement ends with a `;`, a line
+    /// break, or the `}` or end of input closing its scope. The `;` is only
+    /// needed to put several statements on one line.
+    fn parse_statement_end(&mut self) -> Result<(), ParseError> {
+        match self.current().token {
+            Token::Semicolon => {
+                self.consume();
+                Ok(())
+            }
+            Token::RightBraces | Token::EOF => Ok(()),
+            _ if self.follows_line_break() => Ok(()),
+            _ => Err(self.error(&[Token::Semicolon])),
         }
-        self.parse_assignment_expression()
     }
 
-    fn parse_range_expression_if_exists(&mut self) -> Option<ASTExpression> {
-        let start_position = self.current.get_value();
-        let start_expr = self.parse_assignment_expression();
+    fn follows_line_break(&self) -> bool {
+        self.current.get_value() > 0
+            && self.peek(-1).end_position.row < self.current().start_position.row
+    }
 
+    /// Called on a token that could either continue the expression before it
+    /// or start a new statement, such as the `-` in `a\n-1`. Outside of
+    /// brackets a line break in between ends the expression, which is
+    /// reported since the other reading is just as plausible.
+    fn ends_at_line_break(&mut self) -> bool {
+        if self.inside_brackets || !self.follows_line_break() {
+            return false;
+        }
+        let token = self.current();
+        let span = Span::from_token(token);
+        // Every level of a nested binary expression stops at the same token.
+        if self
+            .warnings
+            .last()
+            .is_some_and(|warning| warning.span() == span)
+        {
+            return true;
+        }
+        self.warnings.push(
+            Diagnostic::warning(
+                &format!(
+                    "`{}` at the start of a line begins a new statement",
+                    token.lexeme
+                ),
+                span,
+            )
+            .with_label("not a continuation of the previous line")
+            .with_help(&format!(
+                "to continue the previous line, move `{}` to its end; otherwise end it with `;`",
+                token.lexeme
+            )),
+        );
+        true
+    }
+
+    /// Runs `parse` with line breaks ignored (`inside_brackets`) or ending
+    /// statements, restoring the previous mode afterwards.
+    fn with_brackets<T>(&mut self, inside_brackets: bool, parse: impl FnOnce(&mut Self) -> T) -> T {
+        let outer = std::mem::replace(&mut self.inside_brackets, inside_brackets);
+        let result = parse(self);
+        self.inside_brackets = outer;
+        result
+    }
+
+    fn parse_expression(&mut self) -> Result<ASTExpression, ParseError> {
+        let start_expr = self.parse_assignment_expression()?;
         if self.current().token == Token::Range {
-            self.consume_and_check(Token::Range);
-            let end_expr = self.parse_assignment_expression();
-            return Some(ASTExpression::range(
+            self.consume_and_check(Token::Range)?;
+            let end_expr = self.parse_assignment_expression()?;
+            return Ok(ASTExpression::range(
                 Box::new(start_expr),
                 Box::new(end_expr),
             ));
-        } else {
-            self.current.value.set(start_position);
-            return None;
         }
+        Ok(start_expr)
     }
 
-    fn parse_assignment_expression(&mut self) -> ASTExpression {
-        if self.current().token == Token::Identifier {
-            if self.peek(1).token == Token::LeftSquareBracket {
-                let current_state = self.current.get_value();
-                let identifier = self.consume_and_check(Token::Identifier).clone();
-                let array = ASTExpression::identifier(identifier.clone());
-                let index_expr = self.parse_array_index_expression(array);
-
-                if self.current().token == Token::Assignment {
-                    self.consume_and_check(Token::Assignment);
-                    let expr = self.parse_expression();
-                    return ASTExpression::array_assignment(identifier, index_expr, expr);
-                } else {
-                    self.current.value.set(current_state);
-                    let array = ASTExpression::identifier(
-                        self.consume_and_check(Token::Identifier).clone(),
-                    );
-                    return self.parse_array_index_expression(array);
-                }
-            } else if self.peek(1).token == Token::Assignment {
-                let identifier = self.consume_and_check(Token::Identifier).clone();
-                self.consume_and_check(Token::Assignment);
-                let expr = self.parse_expression();
-                return ASTExpression::assignment(identifier, expr);
-            }
-        }
+    fn parse_assignment_expression(&mut self) -> Result<ASTExpression, ParseError> {
         self.parse_binary_expression(0)
     }
-    fn parse_binary_expression(&mut self, precedence: u8) -> ASTExpression {
-        let mut left = self.parse_unary_expression();
-        while let Some(operator) = self.parse_binary_operator() {
-            let operator_precedence = operator.precedence();
-            if operator_precedence < precedence {
+
+    /// Parses operators binding at least as tight as `min_precedence` with
+    /// precedence climbing, using the table behind
+    /// `ASTBinaryOperatorKind::precedence`. `=` is handled here as the
+    /// loosest, right-associative operator.
+    fn parse_binary_expression(&mut self, min_precedence: u8) -> Result<ASTExpression, ParseError> {
+        let mut left = self.parse_unary_expression()?;
+        loop {
+            if self.current().token == Token::Assignment {
+                if ASSIGNMENT_PRECEDENCE < min_precedence {
+                    break;
+                }
+                let equals = self.consume().clone();
+                let value = self.parse_binary_expression(ASSIGNMENT_PRECEDENCE)?;
+                left = Self::assignment(left, equals, value)?;
+                continue;
+            }
+            if let Some(operator) = self.parse_compound_assignment_operator() {
+                if ASSIGNMENT_PRECEDENCE < min_precedence {
+                    break;
+                }
+                self.consume();
+                let value = self.parse_binary_expression(ASSIGNMENT_PRECEDENCE)?;
+                left = Self::compound_assignment(left, operator, value)?;
+                continue;
+            }
+            let Some(operator) = self.parse_binary_operator() else {
+                break;
+            };
+            let precedence = operator.precedence();
+            if precedence < min_precedence {
+                break;
+            }
+            if operator.kind == ASTBinaryOperatorKind::Subtraction && self.ends_at_line_break() {
                 break;
             }
             self.consume();
-            let right = self.parse_binary_expression(operator_precedence);
+            // Binary operators are left associative, so the right operand
+            // only takes operators that bind tighter.
+            let right = self.parse_binary_expression(precedence + 1)?;
             left = ASTExpression::binary(operator, left, right);
         }
-        return left;
+        return Ok(left);
+    }
+
+    /// Builds the assignment node for `target = value`. Only variables,
+    /// fields and elements of named arrays can be assigned to.
+    fn assignment(
+        target: ASTExpression,
+        equals: TokenInfo,
+        value: ASTExpression,
+    ) -> Result<ASTExpression, ParseError> {
+        match target.kind {
+            ASTExpressionKind::Variable(variable) => {
+                Ok(ASTExpression::assignment(variable.identifier, value))
+            }
+            ASTExpressionKind::FieldAccess(field_access) => {
+                Ok(ASTExpression::field_assignment(field_access, value))
+            }
+            ASTExpressionKind::ArrayIndex(ref array_index) => match &array_index.array.kind {
+                ASTExpressionKind::Variable(variable) => Ok(ASTExpression::array_assignment(
+                    variable.identifier.clone(),
+                    target,
+                    value,
+                )),
+                _ => Err(ParseError::new(vec![Token::Identifier], equals)),
+            },
+            _ => Err(ParseError::new(vec![Token::Identifier], equals)),
+        }
+    }
+
+    fn compound_assignment(
+        target: ASTExpression,
+        operator: ASTBinaryOperator,
+        value: ASTExpression,
+    ) -> Result<ASTExpression, ParseError> {
+        if !target.is_place() {
+            return Err(ParseError::new(vec![Token::Identifier], operator.token));
+        }
+        Ok(ASTExpression::compound_assignment(target, operator, value))
     }
 
-    fn parse_unary_expression(&mut self) -> ASTExpression {
+    fn parse_unary_expression(&mut self) -> Result<ASTExpression, ParseError> {
         if let Some(operator) = self.parse_unary_operator() {
             self.consume();
-            let operand = self.parse_unary_expression();
-            return ASTExpression::unary(operator, operand);
+            let operand = self.parse_unary_expression()?;
+            return Ok(ASTExpression::unary(operator, operand));
         }
         return self.parse_primary_expression();
     }
@@ -295,28 +700,35 @@ impl Node {
         let token = self.current();
         let kind = match token.token {
             Token::Subtraction => Some(ASTUnaryOperatorKind::Subtraction),
+            Token::BwNot => Some(ASTUnaryOperatorKind::BwNot),
+            Token::Not => Some(ASTUnaryOperatorKind::Not),
             _ => None,
         };
         return kind.map(|kind| ASTUnaryOperator::new(kind, token.clone()));
     }
 
-    fn parse_array_expression(&mut self) -> ASTExpression {
-        let mut elements = Vec::new();
-        while self.current().token != Token::RightSquareBracket && !self.is_at_end() {
-            elements.push(self.parse_expression());
-            if self.current().token != Token::RightSquareBracket {
-                self.consume_and_check(Token::Comma);
+    fn parse_array_expression(&mut self) -> Result<ASTExpression, ParseError> {
+        self.with_brackets(true, |parser| {
+            let mut elements = Vec::new();
+            while parser.current().token != Token::RightSquareBracket && !parser.is_at_end() {
+                elements.push(parser.parse_expression()?);
+                if parser.current().token != Token::RightSquareBracket {
+                    parser.consume_and_check_one_of(&[Token::Comma, Token::RightSquareBracket])?;
+                }
             }
-        }
-        self.consume_and_check(Token::RightSquareBracket);
-        ASTExpression::array(elements)
+            parser.consume_and_check(Token::RightSquareBracket)?;
+            Ok(ASTExpression::array(elements))
+        })
     }
 
-    fn parse_array_index_expression(&mut self, array: ASTExpression) -> ASTExpression {
-        self.consume_and_check(Token::LeftSquareBracket);
-        let index = self.parse_expression();
-        self.consume_and_check(Token::RightSquareBracket);
-        ASTExpression::array_index(Box::new(array), Box::new(index))
+    fn parse_array_index_expression(
+        &mut self,
+        array: ASTExpression,
+    ) -> Result<ASTExpression, ParseError> {
+        self.consume_and_check(Token::LeftSquareBracket)?;
+        let index = self.with_brackets(true, |parser| parser.parse_expression())?;
+        self.consume_and_check(Token::RightSquareBracket)?;
+        Ok(ASTExpression::array_index(Box::new(array), Box::new(index)))
     }
 
     fn parse_binary_operator(&mut self) -> Option<ASTBinaryOperator> {
@@ -326,64 +738,194 @@ impl Node {
             Token::Subtraction => Some(ASTBinaryOperatorKind::Subtraction),
             Token::Star => Some(ASTBinaryOperatorKind::Star),
             Token::Division => Some(ASTBinaryOperatorKind::Division),
+            Token::Modulo => Some(ASTBinaryOperatorKind::Modulo),
             Token::BwAnd => Some(ASTBinaryOperatorKind::BwAnd),
             Token::BwOr => Some(ASTBinaryOperatorKind::BwOr),
             Token::BwXor => Some(ASTBinaryOperatorKind::BwXor),
+            Token::BwShl => Some(ASTBinaryOperatorKind::BwShl),
+            Token::BwShr => Some(ASTBinaryOperatorKind::BwShr),
             Token::Equals => Some(ASTBinaryOperatorKind::Equals),
             Token::Inequal => Some(ASTBinaryOperatorKind::Inequal),
             Token::LowerThan => Some(ASTBinaryOperatorKind::LowerThan),
-
             Token::GreaterThan => Some(ASTBinaryOperatorKind::GreaterThan),
+            Token::LowerEquals => Some(ASTBinaryOperatorKind::LowerEquals),
+            Token::GreaterEquals => Some(ASTBinaryOperatorKind::GreaterEquals),
+            Token::And => Some(ASTBinaryOperatorKind::And),
+            Token::Or => Some(ASTBinaryOperatorKind::Or),
 
             _ => None,
         };
         return kind.map(|kind| ASTBinaryOperator::new(kind, token.clone()));
     }
 
-    fn parse_primary_expression(&mut self) -> ASTExpression {
+    fn parse_compound_assignment_operator(&mut self) -> Option<ASTBinaryOperator> {
+        let token = self.current();
+        let kind = match token.token {
+            Token::AdditionAssignment => Some(ASTBinaryOperatorKind::Addition),
+            Token::SubtractionAssignment => Some(ASTBinaryOperatorKind::Subtraction),
+            Token::StarAssignment => Some(ASTBinaryOperatorKind::Star),
+            Token::DivisionAssignment => Some(ASTBinaryOperatorKind::Division),
+            Token::ModuloAssignment => Some(ASTBinaryOperatorKind::Modulo),
+            Token::BwAndAssignment => Some(ASTBinaryOperatorKind::BwAnd),
+            Token::BwOrAssignment => Some(ASTBinaryOperatorKind::BwOr),
+            Token::BwXorAssignment => Some(ASTBinaryOperatorKind::BwXor),
+            Token::BwShlAssignment => Some(ASTBinaryOperatorKind::BwShl),
+            Token::BwShrAssignment => Some(ASTBinaryOperatorKind::BwShr),
+            _ => None,
+        };
+        return kind.map(|kind| ASTBinaryOperator::new(kind, token.clone()));
+    }
+
+    fn parse_primary_expression(&mut self) -> Result<ASTExpression, ParseError> {
+        let expected = [
+            Token::String,
+            Token::Char,
+            Token::Number,
+            Token::True,
+            Token::False,
+            Token::LeftParantheses,
+            Token::LeftSquareBracket,
+            Token::Identifier,
+            Token::Std,
+            Token::Fn,
+        ];
+        if !expected.contains(&self.current().token) {
+            return Err(self.error(&expected));
+        }
+
         let token = self.consume().clone();
-        match token.token {
-            Token::String => ASTExpression::string(token),
+        let expression = match token.token {
+            Token::String | Token::Char => ASTExpression::string(token),
             Token::Number => ASTExpression::number(token),
+            Token::True | Token::False => {
+                let value = token.token == Token::True;
+                ASTExpression::boolean(token, value)
+            }
             Token::LeftParantheses => {
-                let expr = self.parse_expression();
-                self.consume_and_check(Token::RightParantheses);
+                let expr = self.with_brackets(true, |parser| parser.parse_expression())?;
+                self.consume_and_check(Token::RightParantheses)?;
                 ASTExpression::parenthesized(expr)
             }
-            Token::LeftSquareBracket => self.parse_array_expression(),
+            Token::LeftSquareBracket => self.parse_array_expression()?,
+            Token::Fn => self.parse_closure_expression(token)?,
+            Token::Identifier if self.current().token == Token::DoubleColon => {
+                let (qualifier, identifier) = self.parse_path(token)?;
+                let generic_arguments = self.parse_optional_generic_arguments()?;
+                if self.current().token != Token::LeftParantheses {
+                    return Err(self.error(&[Token::LeftParantheses]));
+                }
+                let arguments = self.parse_argument_list()?;
+                ASTExpression::qualified_call(qualifier, identifier, generic_arguments, arguments)
+            }
             Token::Identifier => {
-                if self.current().token == Token::LeftParantheses {
-                    self.parse_call_expression(token)
-                } else if self.current().token == Token::LeftSquareBracket {
-                    let array_expr = ASTExpression::identifier(token);
-                    self.parse_array_index_expression(array_expr)
+                if self.current().token == Token::LeftParantheses && !self.ends_at_line_break() {
+                    self.parse_call_expression(token)?
+                } else if self.is_struct_literal_start(&token) {
+                    self.parse_struct_literal_expression(token)?
                 } else {
                     ASTExpression::identifier(token)
                 }
             }
-            Token::Std => {
-                let double_colon = self.current().clone();
-                self.consume_and_check(Token::DoubleColon);
-                let identifier = self.current().clone();
-                self.consume_and_check(Token::Identifier);
+            _ => {
+                let double_colon = self.consume_and_check(Token::DoubleColon)?.clone();
+                let identifier = self.consume_and_check(Token::Identifier)?.clone();
 
-                self.parse_std_call_expression(token, double_colon, identifier)
+                self.parse_std_call_expression(token, double_colon, identifier)?
             }
-            _ => panic!("Unexpected token: {:?}", token),
+        };
+        self.parse_postfix_expression(expression)
+    }
+
+    /// Parses an anonymous function after its `fn`. Unlike a declaration it
+    /// needs a parameter list, even an empty one, and a block as its body.
+    fn parse_closure_expression(
+        &mut self,
+        fn_keyword: TokenInfo,
+    ) -> Result<ASTExpression, ParseError> {
+        if self.current().token != Token::LeftParantheses {
+            return Err(self.error(&[Token::LeftParantheses]));
+        }
+        let parameters = self.parse_optional_parameter_list()?;
+        let return_type = if self.current().token == Token::Colon {
+            self.consume_and_check(Token::Colon)?;
+            self.parse_type_annotation()?
+        } else {
+            None
+        };
+        if self.current().token != Token::LeftBraces {
+            return Err(self.error(&[Token::LeftBraces]));
         }
+        let body = self.parse_block_statement()?;
+        Ok(ASTExpression::closure(
+            fn_keyword,
+            parameters,
+            return_type,
+            body,
+        ))
     }
 
-    fn parse_call_expression(&mut self, identifier: TokenInfo) -> ASTExpression {
-        self.consume_and_check(Token::LeftParantheses);
-        let mut arguments = Vec::new();
-        while self.current().token != Token::RightParantheses && !self.is_at_end() {
-            arguments.push(self.parse_expression());
-            if self.current().token != Token::RightParantheses {
-                self.consume_and_check(Token::Comma);
+    fn parse_postfix_expression(
+        &mut self,
+        mut expression: ASTExpression,
+    ) -> Result<ASTExpression, ParseError> {
+        loop {
+            let token = self.current().token;
+            match token {
+                Token::Dot => {
+                    let dot = self.consume_and_check(Token::Dot)?.clone();
+                    let field = self.consume_and_check(Token::Identifier)?.clone();
+                    expression = ASTExpression::field_access(expression, dot, field);
+                }
+                Token::LeftSquareBracket if !self.ends_at_line_break() => {
+                    expression = self.parse_array_index_expression(expression)?;
+                }
+                _ => return Ok(expression),
             }
         }
-        self.consume_and_check(Token::RightParantheses);
-        return ASTExpression::call(identifier.clone(), arguments);
+    }
+
+    fn is_struct_literal_start(&self, identifier: &TokenInfo) -> bool {
+        if self.current().token != Token::LeftBraces {
+            return false;
+        }
+        match self.peek(1).token {
+            Token::Identifier => self.peek(2).token == Token::Colon,
+            Token::RightBraces => self.struct_names.contains(&identifier.lexeme),
+            _ => false,
+        }
+    }
+
+    fn parse_struct_literal_expression(
+        &mut self,
+        identifier: TokenInfo,
+    ) -> Result<ASTExpression, ParseError> {
+        self.consume_and_check(Token::LeftBraces)?;
+        let fields = self.with_brackets(true, |parser| {
+            let mut fields = Vec::new();
+            while parser.current().token != Token::RightBraces && !parser.is_at_end() {
+                let field = parser.consume_and_check(Token::Identifier)?.clone();
+                parser.consume_and_check(Token::Colon)?;
+                let expression = parser.parse_expression()?;
+                fields.push(ASTStructLiteralField {
+                    identifier: field,
+                    expression,
+                });
+                if parser.current().token != Token::RightBraces {
+                    parser.consume_and_check_one_of(&[Token::Comma, Token::RightBraces])?;
+                }
+            }
+            Ok(fields)
+        })?;
+        self.consume_and_check(Token::RightBraces)?;
+        Ok(ASTExpression::struct_literal(identifier, fields))
+    }
+
+    fn parse_call_expression(
+        &mut self,
+        identifier: TokenInfo,
+    ) -> Result<ASTExpression, ParseError> {
+        let arguments = self.parse_argument_list()?;
+        return Ok(ASTExpression::call(identifier.clone(), arguments));
     }
 
     fn parse_std_call_expression(
@@ -391,17 +933,30 @@ impl Node {
         std_keyword: TokenInfo,
         double_colon: TokenInfo,
         identifier: TokenInfo,
-    ) -> ASTExpression {
-        self.consume_and_check(Token::LeftParantheses);
-        let mut arguments = Vec::new();
-        while self.current().token != Token::RightParantheses && !self.is_at_end() {
-            arguments.push(self.parse_expression());
-            if self.current().token != Token::RightParantheses {
-                self.consume_and_check(Token::Comma);
+    ) -> Result<ASTExpression, ParseError> {
+        let arguments = self.parse_argument_list()?;
+        return Ok(ASTExpression::std_call(
+            std_keyword,
+            double_colon,
+            identifier,
+            arguments,
+        ));
+    }
+
+    fn parse_argument_list(&mut self) -> Result<Vec<ASTExpression>, ParseError> {
+        self.consume_and_check(Token::LeftParantheses)?;
+        let arguments = self.with_brackets(true, |parser| {
+            let mut arguments = Vec::new();
+            while parser.current().token != Token::RightParantheses && !parser.is_at_end() {
+                arguments.push(parser.parse_expression()?);
+                if parser.current().token != Token::RightParantheses {
+                    parser.consume_and_check_one_of(&[Token::Comma, Token::RightParantheses])?;
+                }
             }
-        }
-        self.consume_and_check(Token::RightParantheses);
-        return ASTExpression::std_call(std_keyword, double_colon, identifier, arguments);
+            Ok(arguments)
+        })?;
+        self.consume_and_check(Token::RightParantheses)?;
+        Ok(arguments)
     }
 
     fn peek(&self, offset: isize) -> &TokenInfo {
@@ -420,11 +975,18 @@ impl Node {
         self.peek(-1)
     }
 
-    fn consume_and_check(&self, kind: Token) -> &TokenInfo {
-        let token = self.consume();
-        if token.token != kind {
-            panic!("Expected token: {:?}, found: {:?}", kind, token.token);
+    fn error(&self, expected: &[Token]) -> ParseError {
+        ParseError::new(expected.to_vec(), self.current().clone())
+    }
+
+    fn consume_and_check(&self, kind: Token) -> Result<&TokenInfo, ParseError> {
+        self.consume_and_check_one_of(&[kind])
+    }
+
+    fn consume_and_check_one_of(&self, kinds: &[Token]) -> Result<&TokenInfo, ParseError> {
+        if !kinds.contains(&self.current().token) {
+            return Err(self.error(kinds));
         }
-        token
+        Ok(self.consume())
     }
 }
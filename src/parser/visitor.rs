@@ -1,10 +1,13 @@
 use crate::parser::{
     ASTArrayAssignmentExpression, ASTArrayExpression, ASTArrayIndexExpression,
     ASTAssignmentExpression, ASTBinaryExpression, ASTBlockStatement, ASTBooleanExpression,
-    ASTCallExpression, ASTExpression, ASTExpressionKind, ASTForStatement, ASTFuncDeclStatement,
-    ASTIfStatement, ASTLetStatement, ASTNumberExpression, ASTParenthesizedExpression,
-    ASTRangeExpression, ASTReturnStatement, ASTStatement, ASTStatementKind, ASTStdCallExpression,
-    ASTStringExpression, ASTTypeAnnotationExpression, ASTUnaryExpression, ASTVariableExpression,
+    ASTCallExpression, ASTClosureExpression, ASTCompoundAssignmentExpression, ASTExpression,
+    ASTExpressionKind, ASTFieldAccessExpression, ASTFieldAssignmentExpression, ASTForStatement,
+    ASTFuncDeclStatement, ASTFunctionTypeExpression, ASTIfStatement, ASTLetStatement,
+    ASTMatchPattern, ASTMatchStatement, ASTNamespaceStatement, ASTNumberExpression,
+    ASTParenthesizedExpression, ASTRangeExpression, ASTReturnStatement, ASTStatement,
+    ASTStatementKind, ASTStdCallExpression, ASTStringExpression, ASTStructLiteralExpression,
+    ASTStructStatement, ASTTypeAnnotationExpression, ASTUnaryExpression, ASTVariableExpression,
     ASTWhileStatement,
 };
 
@@ -35,6 +38,15 @@ pub trait ASTVisitor<'a> {
             ASTStatementKind::For(stmt) => {
                 self.visit_for_statement(stmt);
             }
+            ASTStatementKind::Match(stmt) => {
+                self.visit_match_statement(stmt);
+            }
+            ASTStatementKind::Struct(stmt) => {
+                self.visit_struct_statement(stmt);
+            }
+            ASTStatementKind::Namespace(stmt) => {
+                self.visit_namespace_statement(stmt);
+            }
         }
     }
 
@@ -70,6 +82,25 @@ pub trait ASTVisitor<'a> {
         }
     }
 
+    fn visit_match_statement(&mut self, match_statement: &ASTMatchStatement) {
+        self.visit_expression(&match_statement.scrutinee);
+        for arm in &match_statement.arms {
+            match &arm.pattern {
+                ASTMatchPattern::Literal(expr) => self.visit_expression(expr),
+                ASTMatchPattern::Range(expr) => self.visit_range_expression(expr),
+                ASTMatchPattern::Binding(_) | ASTMatchPattern::Wildcard(_) => {}
+            }
+            self.visit_statement(&arm.body);
+        }
+        if let Some(else_branch) = &match_statement.else_branch {
+            self.visit_statement(&else_branch.else_statement);
+        }
+    }
+
+    fn visit_struct_statement(&mut self, _struct_statement: &ASTStructStatement) {}
+
+    fn visit_namespace_statement(&mut self, _namespace_statement: &ASTNamespaceStatement) {}
+
     fn visit_let_statement(&mut self, let_statement: &ASTLetStatement);
     fn visit_statement(&mut self, statement: &ASTStatement) {
         self.do_visit_statement(statement);
@@ -124,6 +155,24 @@ pub trait ASTVisitor<'a> {
             ASTExpressionKind::TypeAnnotation(expr) => {
                 self.visit_type_annotation_expression(expr);
             }
+            ASTExpressionKind::StructLiteral(expr) => {
+                self.visit_struct_literal_expression(expr);
+            }
+            ASTExpressionKind::FieldAccess(expr) => {
+                self.visit_field_access_expression(expr);
+            }
+            ASTExpressionKind::FieldAssignment(expr) => {
+                self.visit_field_assignment_expression(expr);
+            }
+            ASTExpressionKind::CompoundAssignment(expr) => {
+                self.visit_compound_assignment_expression(expr);
+            }
+            ASTExpressionKind::Closure(expr) => {
+                self.visit_closure_expression(expr);
+            }
+            ASTExpressionKind::FunctionType(expr) => {
+                self.visit_function_type_expression(expr);
+            }
         }
     }
     fn visit_call_expression(&mut self, call_expression: &ASTCallExpression) {
@@ -152,6 +201,48 @@ pub trait ASTVisitor<'a> {
         self.visit_expression(&array_assignment_expression.expression);
     }
 
+    fn visit_struct_literal_expression(
+        &mut self,
+        struct_literal_expression: &ASTStructLiteralExpression,
+    ) {
+        for field in &struct_literal_expression.fields {
+            self.visit_expression(&field.expression);
+        }
+    }
+
+    fn visit_field_access_expression(
+        &mut self,
+        field_access_expression: &ASTFieldAccessExpression,
+    ) {
+        self.visit_expression(&field_access_expression.object);
+    }
+
+    fn visit_field_assignment_expression(
+        &mut self,
+        field_assignment_expression: &ASTFieldAssignmentExpression,
+    ) {
+        self.visit_field_access_expression(&field_assignment_expression.field_access);
+        self.visit_expression(&field_assignment_expression.expression);
+    }
+
+    fn visit_compound_assignment_expression(
+        &mut self,
+        compound_assignment_expression: &ASTCompoundAssignmentExpression,
+    ) {
+        self.visit_expression(&compound_assignment_expression.target);
+        self.visit_expression(&compound_assignment_expression.expression);
+    }
+
+    fn visit_closure_expression(&mut self, closure_expression: &ASTClosureExpression) {
+        self.visit_statement(&closure_expression.body);
+    }
+
+    fn visit_function_type_expression(
+        &mut self,
+        _function_type_expression: &ASTFunctionTypeExpression,
+    ) {
+    }
+
     fn visit_range_expression(&mut self, range_expression: &ASTRangeExpression) {
         self.visit_expression(&range_expression.start);
         self.visit_expression(&range_expression.end);
@@ -0,0 +1,133 @@
+use crate::diagnostics::Diagnostic;
+use crate::evaluator::Session;
+use std::io::{self, BufRead, Write};
+
+const HELP: &str = "\
+:type <expr>  show the type of an expression
+:cpp <code>   show the C++ emitted for a snippet
+:reset        forget all declarations
+:help         show this message
+:quit         exit the REPL";
+
+/// Whether `input` can be evaluated, i.e. every brace, parenthesis and
+/// bracket outside of string and char literals and comments is closed.
+pub fn is_complete(input: &str) -> bool {
+    let mut depth = 0;
+    // The closing quote of the literal being read, if any.
+    let mut quote = None;
+    let mut escaped = false;
+    let mut in_line_comment = false;
+    // Block comments nest, as in the tokenizer.
+    let mut comment_depth = 0;
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_line_comment {
+            in_line_comment = c != '\n';
+            continue;
+        }
+        if comment_depth > 0 {
+            match (c, chars.peek()) {
+                ('/', Some('*')) => {
+                    chars.next();
+                    comment_depth += 1;
+                }
+                ('*', Some('/')) => {
+                    chars.next();
+                    comment_depth -= 1;
+                }
+                _ => {}
+            }
+            continue;
+        }
+        if let Some(end) = quote {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                _ if c == end => quote = None,
+                _ => {}
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('/', Some('/')) => in_line_comment = true,
+            ('/', Some('*')) => {
+                chars.next();
+                comment_depth += 1;
+            }
+            ('"' | '\'', _) => quote = Some(c),
+            ('{' | '(' | '[', _) => depth += 1,
+            ('}' | ')' | ']', _) => depth -= 1,
+            _ => {}
+        }
+    }
+    depth <= 0
+}
+
+/// Handles one complete input and returns what should be printed.
+pub fn evaluate(session: &mut Session, input: &str) -> String {
+    let (command, argument) = match input.strip_prefix(':') {
+        Some(rest) => match rest.split_once(char::is_whitespace) {
+            Some((command, argument)) => (command, argument.trim()),
+            None => (rest, ""),
+        },
+        None => ("", input),
+    };
+
+    let result = match command {
+        "" => session.eval(argument).map(Option::unwrap_or_default),
+        "type" | "t" => session.type_of(argument),
+        "cpp" => session.cpp(argument),
+        "reset" => {
+            session.reset();
+            Ok(String::new())
+        }
+        "help" | "h" => Ok(String::from(HELP)),
+        _ => Ok(format!("unknown command `:{}`, try :help", command)),
+    };
+
+    match result {
+        Ok(output) => output,
+        Err(diagnostics) => render(&diagnostics, argument),
+    }
+}
+
+fn render(diagnostics: &[Diagnostic], source: &str) -> String {
+    diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.render("<repl>", source))
+        .collect::<Vec<String>>()
+        .join("\n")
+        .trim_end()
+        .to_string()
+}
+
+pub fn start() {
+    let mut session = Session::new();
+    let stdin = io::stdin();
+    let mut input = String::new();
+    loop {
+        print!("{}", if input.is_empty() { ">> " } else { ".. " });
+        io::stdout().flush().unwrap();
+
+        let mut line = String::new();
+        match stdin.lock().read_line(&mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => input.push_str(&line),
+        }
+        if !is_complete(&input) {
+            continue;
+        }
+
+        let source = std::mem::take(&mut input);
+        let source = source.trim();
+        match source {
+            "" => continue,
+            ":quit" | ":q" => break,
+            _ => {}
+        }
+        let output = evaluate(&mut session, source);
+        if !output.is_empty() {
+            println!("{}", output);
+        }
+    }
+}
@@ -1,11 +1,18 @@
 use std::io::BufRead;
+use std::sync::LazyLock;
+use unicode_xid::UnicodeXID;
 
 #[derive(Debug)]
 pub enum Error {
     NotAKeyword(String),
     InvalidPattern(String, Position),
     InvalidStream,
+    InvalidUtf8(Position),
     UnclosedString(Position),
+    UnclosedComment(Position),
+    InvalidEscape(String, Position),
+    InvalidChar(String, Position),
+    InvalidNumber(String, Position),
 }
 
 impl std::error::Error for Error {}
@@ -16,14 +23,39 @@ impl std::fmt::Display for Error {
             Error::NotAKeyword(lexeme) => write!(f, "Tokenizer error: not a keyword {}", lexeme),
             Error::InvalidPattern(lexeme, position) => write!(
                 f,
-                "Tokenizer error: invalid pattern {} on line {}",
-                lexeme, position.row
+                "Tokenizer error: invalid pattern {} on line {}, column {}",
+                lexeme, position.row, position.col
             ),
             Error::InvalidStream => write!(f, "Tokenizer error: invalid stream. Cannot read"),
+            Error::InvalidUtf8(position) => write!(
+                f,
+                "Tokenizer error: invalid UTF-8 on line {}, column {}",
+                position.row, position.col
+            ),
             Error::UnclosedString(position) => write!(
                 f,
-                "Tokenizer error: Unclosed string on line {}",
-                position.row
+                "Tokenizer error: Unclosed string on line {}, column {}",
+                position.row, position.col
+            ),
+            Error::UnclosedComment(position) => write!(
+                f,
+                "Tokenizer error: Unclosed comment on line {}, column {}",
+                position.row, position.col
+            ),
+            Error::InvalidEscape(sequence, position) => write!(
+                f,
+                "Tokenizer error: invalid escape sequence {} on line {}, column {}",
+                sequence, position.row, position.col
+            ),
+            Error::InvalidChar(lexeme, position) => write!(
+                f,
+                "Tokenizer error: invalid char literal {} on line {}, column {}",
+                lexeme, position.row, position.col
+            ),
+            Error::InvalidNumber(lexeme, position) => write!(
+                f,
+                "Tokenizer error: invalid number literal {} on line {}, column {}",
+                lexeme, position.row, position.col
             ),
         }
     }
@@ -44,6 +76,11 @@ pub enum Token {
     Star,
     Modulo,
     String,
+    StringEscape,
+    Char,
+    CharEscape,
+    LineComment,
+    BlockComment,
 
     Let,
     Mut,
@@ -52,6 +89,9 @@ pub enum Token {
     Return,
     Match,
     Struct,
+    Namespace,
+    True,
+    False,
     Arrow,
 
     BwAnd,
@@ -62,6 +102,7 @@ pub enum Token {
     BwShr,
     And,
     Or,
+    Not,
 
     Multiplication,
     Division,
@@ -75,8 +116,20 @@ pub enum Token {
     LeftBraces,
     RightBraces,
     Assignment,
+    AdditionAssignment,
+    SubtractionAssignment,
+    StarAssignment,
+    DivisionAssignment,
+    ModuloAssignment,
+    BwAndAssignment,
+    BwOrAssignment,
+    BwXorAssignment,
+    BwShlAssignment,
+    BwShrAssignment,
     GreaterThan,
     LowerThan,
+    GreaterEquals,
+    LowerEquals,
     Equals,
     Inequal,
     Semicolon,
@@ -99,6 +152,55 @@ const MAX_STATE: usize = Token::Error as usize;
 pub struct Position {
     pub row: u32,
     pub col: u32,
+    /// Byte offset into the source.
+    pub offset: usize,
+}
+
+impl Position {
+    pub fn new(row: u32, col: u32, offset: usize) -> Self {
+        Position { row, col, offset }
+    }
+
+    /// The position after `text`, when `text` starts at this position.
+    pub fn advance(self, text: &str) -> Position {
+        text.chars().fold(self, next_position)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TriviaKind {
+    LineComment,
+    BlockComment,
+    DocComment,
+}
+
+/// A comment the parser never sees. It is attached to the token it belongs
+/// to instead, so later stages can still reproduce it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trivia {
+    pub kind: TriviaKind,
+    pub text: String,
+    pub start_position: Position,
+    pub end_position: Position,
+}
+
+impl From<TokenInfo> for Trivia {
+    fn from(token_info: TokenInfo) -> Self {
+        let kind = match token_info.token {
+            Token::BlockComment => TriviaKind::BlockComment,
+            _ if token_info.lexeme.starts_with("///") && !token_info.lexeme.starts_with("////") => {
+                TriviaKind::DocComment
+            }
+            _ => TriviaKind::LineComment,
+        };
+
+        Trivia {
+            kind,
+            text: token_info.lexeme,
+            start_position: token_info.start_position,
+            end_position: token_info.end_position,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Default)]
@@ -106,66 +208,121 @@ pub struct TokenInfo {
     pub token: Token,
     pub lexeme: String,
     pub start_position: Position,
+    pub end_position: Position,
+    /// Comments on the lines before this token.
+    pub leading_trivia: Vec<Trivia>,
+    /// Comments following this token on the line it ends on.
+    pub trailing_trivia: Vec<Trivia>,
 }
 
 struct DFA {
-    num_states: usize,
-    alphabet: [char; 256],
     last: char,
     position: Position,
 }
 
-pub fn tokenize<R: BufRead>(mut tokens_reader: R) -> Result<Vec<TokenInfo>, Error> {
-    let mut dfa = DFA {
-        num_states: MAX_STATE,
-        alphabet: [char::default(); 256],
-        last: char::default(),
-        position: Position { row: 1, col: 1 },
-    };
-
-    let mut vec = Vec::new();
+const ALPHABET_LEN: usize = 128;
+
+static TRANSITIONS_TABLE: LazyLock<Vec<Vec<Token>>> =
+    LazyLock::new(|| create_transitions_table(ALPHABET_LEN, MAX_STATE));
+
+/// Streams the tokens of a source, ending with a `Token::EOF` token. A token
+/// is only yielded once the next one has been read, because a comment after
+/// it on the same line still has to be attached as trailing trivia.
+pub struct Tokens<R: BufRead> {
+    tokens_reader: R,
+    dfa: DFA,
+    pending: Option<TokenInfo>,
+    leading_trivia: Vec<Trivia>,
+    // An error hit while reading ahead, reported after the pending token.
+    error: Option<Error>,
+    finished: bool,
+}
 
-    for i in 0..=255 {
-        dfa.alphabet[i] = char::from_u32(i as u32).unwrap();
+pub fn tokenize<R: BufRead>(tokens_reader: R) -> Tokens<R> {
+    Tokens {
+        tokens_reader,
+        dfa: DFA {
+            last: char::default(),
+            position: Position::new(1, 1, 0),
+        },
+        pending: None,
+        leading_trivia: Vec::new(),
+        error: None,
+        finished: false,
     }
+}
 
-    match get_token(&mut tokens_reader, &mut dfa) {
-        Ok(mut token_info) => {
-            while token_info.token != Token::EOF {
-                if token_info.token != Token::None {
-                    vec.push(token_info);
-                }
+impl<R: BufRead> Iterator for Tokens<R> {
+    type Item = Result<TokenInfo, Error>;
 
-                token_info = match get_token(&mut tokens_reader, &mut dfa) {
-                    Ok(token_info) => token_info,
-                    Err(error) => return Err(error),
-                }
-            }
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(error) = self.error.take() {
+            return Some(Err(error));
         }
 
-        Err(error) => return Err(error),
-    };
+        while !self.finished {
+            if self
+                .pending
+                .as_ref()
+                .is_some_and(|token_info| token_info.token == Token::EOF)
+            {
+                self.finished = true;
+                return self.pending.take().map(Ok);
+            }
 
-    vec.push(TokenInfo {
-        token: Token::EOF,
-        lexeme: String::from(""),
-        start_position: dfa.position,
-    });
+            let mut token_info = match get_token(&mut self.tokens_reader, &mut self.dfa) {
+                Ok(token_info) => token_info,
+                Err(error) => {
+                    self.finished = true;
+                    return match self.pending.take() {
+                        Some(previous) => {
+                            self.error = Some(error);
+                            Some(Ok(previous))
+                        }
+                        None => Some(Err(error)),
+                    };
+                }
+            };
+
+            match token_info.token {
+                Token::None => {}
+                Token::LineComment | Token::BlockComment => {
+                    let trivia = Trivia::from(token_info);
+                    match self.pending.as_mut() {
+                        Some(previous)
+                            if self.leading_trivia.is_empty()
+                                && previous.end_position.row == trivia.start_position.row =>
+                        {
+                            previous.trailing_trivia.push(trivia)
+                        }
+                        _ => self.leading_trivia.push(trivia),
+                    }
+                }
+                _ => {
+                    token_info.leading_trivia = std::mem::take(&mut self.leading_trivia);
+                    if let Some(previous) = self.pending.replace(token_info) {
+                        return Some(Ok(previous));
+                    }
+                }
+            }
+        }
 
-    Ok(vec)
+        None
+    }
 }
 
 fn get_token<R: BufRead>(mut tokens_reader: R, dfa: &mut DFA) -> Result<TokenInfo, Error> {
-    let transitions_table = create_transitions_table(dfa.alphabet.len(), dfa.num_states);
-    let mut buffer = [0; 1];
     let mut token_info = TokenInfo {
         token: Token::None,
         lexeme: String::from(""),
         start_position: dfa.position,
+        end_position: dfa.position,
+        ..Default::default()
     };
 
     let mut state = Token::None;
     let mut code: char;
+    let mut terminated = false;
 
     if dfa.last != char::default() {
         code = dfa.last;
@@ -174,9 +331,8 @@ fn get_token<R: BufRead>(mut tokens_reader: R, dfa: &mut DFA) -> Result<TokenInf
             token_info.start_position = prev_position(dfa.position, code);
         }
     } else {
-        if tokens_reader.read(&mut buffer).unwrap() > 0 {
-            code = buffer[0] as char;
-            dfa.position = next_position(dfa.position, code);
+        if let Some(next) = read_char(&mut tokens_reader, &mut dfa.position)? {
+            code = next;
         } else {
             token_info.token = Token::EOF;
             return Ok(token_info);
@@ -200,7 +356,21 @@ fn get_token<R: BufRead>(mut tokens_reader: R, dfa: &mut DFA) -> Result<TokenInf
     }*/
 
     loop {
-        let next_state = transitions_table[state as usize][code as usize];
+        if state == Token::Identifier && token_info.lexeme == "r" && (code == '\"' || code == '#') {
+            read_raw_string(&mut tokens_reader, dfa, &mut token_info, code)?;
+            state = Token::String;
+            terminated = true;
+            break;
+        }
+
+        let mut next_state = transition(&TRANSITIONS_TABLE, state, code);
+        if state == Token::Number
+            && next_state == Token::None
+            && continues_number(&token_info.lexeme, code, &mut tokens_reader)
+        {
+            next_state = Token::Number;
+        }
+
         if next_state == Token::EOT || next_state == Token::EOF {
             break;
         }
@@ -214,13 +384,13 @@ fn get_token<R: BufRead>(mut tokens_reader: R, dfa: &mut DFA) -> Result<TokenInf
         }
 
         if next_state == Token::None {
-            if state == Token::String {
+            if state == Token::String || state == Token::Char {
+                terminated = true;
                 token_info.lexeme.push(code);
                 token_info.token = state;
-                if tokens_reader.read(&mut buffer).unwrap() > 0 {
-                    code = buffer[0] as char;
+                if let Some(next) = read_char(&mut tokens_reader, &mut dfa.position)? {
+                    code = next;
                     dfa.last = code;
-                    dfa.position = next_position(dfa.position, code);
                 } else {
                     if !token_info.lexeme.is_empty() {
                         dfa.last = char::default();
@@ -236,10 +406,14 @@ fn get_token<R: BufRead>(mut tokens_reader: R, dfa: &mut DFA) -> Result<TokenInf
         state = next_state;
         token_info.lexeme.push(code);
 
-        if tokens_reader.read(&mut buffer).unwrap() > 0 {
-            code = buffer[0] as char;
+        if state == Token::BlockComment {
+            read_block_comment(&mut tokens_reader, dfa, &mut token_info)?;
+            break;
+        }
+
+        if let Some(next) = read_char(&mut tokens_reader, &mut dfa.position)? {
+            code = next;
             dfa.last = code;
-            dfa.position = next_position(dfa.position, code);
         } else {
             if !token_info.lexeme.is_empty() {
                 dfa.last = char::default();
@@ -252,14 +426,336 @@ fn get_token<R: BufRead>(mut tokens_reader: R, dfa: &mut DFA) -> Result<TokenInf
 
     token_info.token = state;
     token_info.token = assign_if_reserved_identifier(&token_info);
-
-    if token_info.token == Token::String && token_info.lexeme.chars().last().unwrap() != '\"' {
-        return Err(Error::UnclosedString(token_info.start_position));
+    token_info.end_position = token_info
+        .lexeme
+        .chars()
+        .fold(token_info.start_position, next_position);
+
+    match token_info.token {
+        Token::String | Token::StringEscape if !terminated => {
+            return Err(Error::UnclosedString(token_info.start_position));
+        }
+        Token::Char | Token::CharEscape if !terminated => {
+            return Err(Error::InvalidChar(
+                token_info.lexeme,
+                token_info.start_position,
+            ));
+        }
+        Token::Number if number_literal(&token_info.lexeme).is_none() => {
+            return Err(Error::InvalidNumber(
+                token_info.lexeme,
+                token_info.start_position,
+            ));
+        }
+        Token::String | Token::Char if !token_info.lexeme.starts_with('r') => {
+            match decode_literal(&token_info.lexeme) {
+                Ok(value) if token_info.token == Token::Char && value.chars().count() != 1 => {
+                    return Err(Error::InvalidChar(
+                        token_info.lexeme,
+                        token_info.start_position,
+                    ));
+                }
+                Ok(_) => {}
+                Err((offset, sequence)) => {
+                    let position = token_info.lexeme[..offset]
+                        .chars()
+                        .fold(token_info.start_position, next_position);
+                    return Err(Error::InvalidEscape(sequence, position));
+                }
+            }
+        }
+        _ => {}
     }
 
     Ok(token_info)
 }
 
+/// Reads the rest of a block comment after its opening `/*`. Block comments
+/// nest, so the comment only ends once every `/*` has a matching `*/`.
+fn read_block_comment<R: BufRead>(
+    mut tokens_reader: R,
+    dfa: &mut DFA,
+    token_info: &mut TokenInfo,
+) -> Result<(), Error> {
+    let mut depth = 1;
+    let mut previous = char::default();
+
+    while depth > 0 {
+        let Some(code) = read_char(&mut tokens_reader, &mut dfa.position)? else {
+            return Err(Error::UnclosedComment(token_info.start_position));
+        };
+        token_info.lexeme.push(code);
+
+        previous = match (previous, code) {
+            ('/', '*') => {
+                depth += 1;
+                char::default()
+            }
+            ('*', '/') => {
+                depth -= 1;
+                char::default()
+            }
+            _ => code,
+        };
+    }
+
+    dfa.last = char::default();
+    Ok(())
+}
+
+/// Reads the next UTF-8 encoded character and moves `position` past it.
+/// The bytes are taken straight from the reader's buffer, which a character
+/// may straddle the end of.
+fn read_char<R: BufRead>(
+    mut tokens_reader: R,
+    position: &mut Position,
+) -> Result<Option<char>, Error> {
+    let mut buffer = [0; 4];
+    let mut len = 0;
+    let mut needed = 1;
+    while len < needed {
+        let available = tokens_reader
+            .fill_buf()
+            .map_err(|_| Error::InvalidStream)?;
+        if available.is_empty() {
+            return match len {
+                0 => Ok(None),
+                _ => Err(Error::InvalidUtf8(*position)),
+            };
+        }
+        if len == 0 {
+            needed = match available[0] {
+                0x00..=0x7f => 1,
+                0xc0..=0xdf => 2,
+                0xe0..=0xef => 3,
+                0xf0..=0xf7 => 4,
+                _ => {
+                    tokens_reader.consume(1);
+                    return Err(Error::InvalidUtf8(*position));
+                }
+            };
+        }
+        let count = (needed - len).min(available.len());
+        buffer[len..len + count].copy_from_slice(&available[..count]);
+        tokens_reader.consume(count);
+        len += count;
+    }
+    let code = std::str::from_utf8(&buffer[..len])
+        .map_err(|_| Error::InvalidUtf8(*position))?
+        .chars()
+        .next()
+        .unwrap();
+
+    *position = next_position(*position, code);
+    Ok(Some(code))
+}
+
+/// Looks up the next state. The table covers ASCII; everything beyond it is
+/// classified by Unicode properties instead.
+fn transition(transitions_table: &[Vec<Token>], state: Token, code: char) -> Token {
+    if code.is_ascii() {
+        return transitions_table[state as usize][code as usize];
+    }
+
+    match state {
+        Token::String | Token::StringEscape => Token::String,
+        Token::Char | Token::CharEscape => Token::Char,
+        Token::LineComment => Token::LineComment,
+        Token::None if code.is_xid_start() => Token::Identifier,
+        Token::Identifier if code.is_xid_continue() => Token::Identifier,
+        _ => Token::None,
+    }
+}
+
+/// Whether `code` continues the number literal in `lexeme` even though the
+/// transitions table ends it there: the `.` of a float, which needs a digit
+/// after it so that `0..3` stays a range, or the sign of an exponent.
+fn continues_number<R: BufRead>(lexeme: &str, code: char, mut tokens_reader: R) -> bool {
+    match code {
+        '.' => {
+            lexeme.chars().all(|c| c.is_ascii_digit() || c == '_')
+                && tokens_reader
+                    .fill_buf()
+                    .map(|buffer| buffer.first().is_some_and(u8::is_ascii_digit))
+                    .unwrap_or(false)
+        }
+        '+' | '-' => {
+            !lexeme.starts_with("0x") && (lexeme.ends_with('e') || lexeme.ends_with('E'))
+        }
+        _ => false,
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum NumberValue {
+    Int(u64),
+    Float(f64),
+}
+
+const INTEGER_SUFFIXES: [&str; 8] = ["u8", "u16", "u32", "u64", "i8", "i16", "i32", "i64"];
+const FLOAT_SUFFIXES: [&str; 2] = ["f32", "f64"];
+
+/// Parses a number literal into its value and type suffix, if it has one.
+/// Returns `None` for malformed literals and for values that do not fit their
+/// suffix. Signed suffixes allow one past their maximum so that the minimum
+/// can be written with a unary minus.
+pub fn number_literal(lexeme: &str) -> Option<(NumberValue, Option<&str>)> {
+    let (radix, digits) = match lexeme.get(..2) {
+        Some("0x") => (16, &lexeme[2..]),
+        Some("0b") => (2, &lexeme[2..]),
+        Some("0o") => (8, &lexeme[2..]),
+        _ => (10, lexeme),
+    };
+
+    // Float suffixes would read as hex digits, so only decimals can have them.
+    let float_suffixes: &[&str] = if radix == 10 { &FLOAT_SUFFIXES } else { &[] };
+    let suffix = INTEGER_SUFFIXES
+        .iter()
+        .chain(float_suffixes)
+        .find(|suffix| digits.ends_with(*suffix))
+        .copied();
+    let body: String = digits[..digits.len() - suffix.map_or(0, str::len)]
+        .chars()
+        .filter(|&c| c != '_')
+        .collect();
+    if body.is_empty() {
+        return None;
+    }
+
+    let is_float_suffix = suffix.is_some_and(|suffix| suffix.starts_with('f'));
+    if radix == 10 && (is_float_suffix || body.contains(['.', 'e', 'E'])) {
+        if suffix.is_some() && !is_float_suffix {
+            return None;
+        }
+        return body
+            .parse::<f64>()
+            .ok()
+            .filter(|value| value.is_finite())
+            .map(|value| (NumberValue::Float(value), suffix));
+    }
+
+    let value = u64::from_str_radix(&body, radix).ok()?;
+    if let Some(suffix) = suffix {
+        let bits: u32 = suffix[1..].parse().unwrap();
+        let max = if suffix.starts_with('u') {
+            u64::MAX >> (64 - bits)
+        } else {
+            // The magnitude of the minimum, as in `-128i8`. The type checker
+            // rejects it when the literal is not negated.
+            1 << (bits - 1)
+        };
+        if value > max {
+            return None;
+        }
+    }
+
+    Some((NumberValue::Int(value), suffix))
+}
+
+/// Reads the rest of a raw string after its `r` prefix. Raw strings may be
+/// fenced with any number of `#`, and nothing inside them is escaped.
+fn read_raw_string<R: BufRead>(
+    mut tokens_reader: R,
+    dfa: &mut DFA,
+    token_info: &mut TokenInfo,
+    first: char,
+) -> Result<(), Error> {
+    let mut code = first;
+    let mut hashes = 0;
+
+    while code == '#' {
+        token_info.lexeme.push(code);
+        hashes += 1;
+        code = match read_char(&mut tokens_reader, &mut dfa.position)? {
+            Some(code) => code,
+            None => return Err(Error::UnclosedString(token_info.start_position)),
+        };
+    }
+
+    if code != '\"' {
+        token_info.lexeme.push(code);
+        return Err(Error::InvalidPattern(
+            token_info.lexeme.clone(),
+            token_info.start_position,
+        ));
+    }
+    token_info.lexeme.push(code);
+
+    let opening_len = token_info.lexeme.len();
+    let closing = format!("\"{}", "#".repeat(hashes));
+    while token_info.lexeme.len() <= opening_len || !token_info.lexeme.ends_with(&closing) {
+        code = match read_char(&mut tokens_reader, &mut dfa.position)? {
+            Some(code) => code,
+            None => return Err(Error::UnclosedString(token_info.start_position)),
+        };
+        token_info.lexeme.push(code);
+    }
+
+    dfa.last = char::default();
+    Ok(())
+}
+
+/// Returns the value of a string or char literal: the text between its
+/// quotes, with escape sequences decoded. Raw strings are returned verbatim.
+pub fn literal_value(lexeme: &str) -> String {
+    decode_literal(lexeme).unwrap_or_else(|_| lexeme.to_string())
+}
+
+/// Decodes a string or char literal. On failure, returns the byte offset and
+/// text of the first invalid escape sequence.
+fn decode_literal(lexeme: &str) -> Result<String, (usize, String)> {
+    if let Some(raw) = lexeme.strip_prefix('r') {
+        let hashes = raw.len() - raw.trim_start_matches('#').len();
+        return Ok(raw[hashes + 1..raw.len() - hashes - 1].to_string());
+    }
+
+    let mut value = String::new();
+    let mut chars = lexeme[1..lexeme.len() - 1].char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+
+        let offset = index + 1;
+        let invalid = |len: usize| (offset, lexeme[offset..offset + len].to_string());
+        let decoded = match chars.next() {
+            Some((_, 'n')) => '\n',
+            Some((_, 't')) => '\t',
+            Some((_, 'r')) => '\r',
+            Some((_, '0')) => '\0',
+            Some((_, '\\')) => '\\',
+            Some((_, '\"')) => '\"',
+            Some((_, '\'')) => '\'',
+            Some((_, 'u')) => {
+                let mut len = 2;
+                if chars.next_if(|&(_, c)| c == '{').is_none() {
+                    return Err(invalid(len));
+                }
+                len += 1;
+                let mut digits = String::new();
+                while let Some((_, c)) = chars.next_if(|&(_, c)| c.is_ascii_hexdigit()) {
+                    digits.push(c);
+                    len += 1;
+                }
+                if chars.next_if(|&(_, c)| c == '}').is_none() || digits.is_empty() {
+                    return Err(invalid(len));
+                }
+                len += 1;
+                match u32::from_str_radix(&digits, 16).ok().and_then(char::from_u32) {
+                    Some(c) => c,
+                    None => return Err(invalid(len)),
+                }
+            }
+            Some((_, c)) => return Err(invalid(1 + c.len_utf8())),
+            None => return Err(invalid(1)),
+        };
+        value.push(decoded);
+    }
+
+    Ok(value)
+}
+
 fn assign_if_reserved_identifier(token_info: &TokenInfo) -> Token {
     match token_info.lexeme.as_ref() {
         "for" => Token::For,
@@ -274,7 +770,10 @@ fn assign_if_reserved_identifier(token_info: &TokenInfo) -> Token {
         "interpret" => Token::Interpret,
         "match" => Token::Match,
         "struct" => Token::Struct,
+        "namespace" => Token::Namespace,
         "std" => Token::Std,
+        "true" => Token::True,
+        "false" => Token::False,
         _ => token_info.token,
     }
 }
@@ -309,9 +808,12 @@ fn create_transitions_table(alphabet_len: usize, num_states: usize) -> Vec<Vec<T
     set_transition(Token::None, '*', Token::Star);
     set_transition(Token::None, '%', Token::Modulo);
     set_transition(Token::None, '/', Token::Division);
+    set_transition(Token::Division, '/', Token::LineComment);
+    set_transition(Token::Division, '*', Token::BlockComment);
     set_transition(Token::None, '+', Token::Addition);
     set_transition(Token::None, '-', Token::Subtraction);
     set_transition(Token::None, ',', Token::Comma);
+    set_transition(Token::None, ';', Token::Semicolon);
 
     set_transition(Token::None, ':', Token::Colon);
     set_transition(Token::Colon, ':', Token::DoubleColon);
@@ -322,6 +824,11 @@ fn create_transitions_table(alphabet_len: usize, num_states: usize) -> Vec<Vec<T
 
     set_transition(Token::LowerThan, '<', Token::BwShl);
     set_transition(Token::GreaterThan, '>', Token::BwShr);
+    set_transition(Token::LowerThan, '=', Token::LowerEquals);
+    set_transition(Token::GreaterThan, '=', Token::GreaterEquals);
+
+    set_transition(Token::None, '!', Token::Not);
+    set_transition(Token::Not, '=', Token::Inequal);
 
     set_transition(Token::None, '&', Token::BwAnd);
     set_transition(Token::None, '|', Token::BwOr);
@@ -331,16 +838,42 @@ fn create_transitions_table(alphabet_len: usize, num_states: usize) -> Vec<Vec<T
     set_transition(Token::BwAnd, '&', Token::And);
     set_transition(Token::BwOr, '|', Token::Or);
 
+    set_transition(Token::Addition, '=', Token::AdditionAssignment);
+    set_transition(Token::Subtraction, '=', Token::SubtractionAssignment);
+    set_transition(Token::Star, '=', Token::StarAssignment);
+    set_transition(Token::Division, '=', Token::DivisionAssignment);
+    set_transition(Token::Modulo, '=', Token::ModuloAssignment);
+    set_transition(Token::BwAnd, '=', Token::BwAndAssignment);
+    set_transition(Token::BwOr, '=', Token::BwOrAssignment);
+    set_transition(Token::BwXor, '=', Token::BwXorAssignment);
+    set_transition(Token::BwShl, '=', Token::BwShlAssignment);
+    set_transition(Token::BwShr, '=', Token::BwShrAssignment);
+
     set_transition(Token::None, '\"', Token::String);
     //set_transition(Token::String::string_start, '\"', Token::String::string_end);
 
-    //TODO add to stop on " and not on \"
-    for i in 0..=255 {
+    set_transition(Token::None, '\'', Token::Char);
+
+    for i in 0..alphabet_len {
         let c = char::from_u32(i as u32).unwrap();
-        if c != '\"' {
-            set_transition(Token::String, c, Token::String)
-        } else {
-            set_transition(Token::String, c, Token::None)
+        set_transition(Token::StringEscape, c, Token::String);
+        set_transition(Token::CharEscape, c, Token::Char);
+        match c {
+            '\"' => set_transition(Token::String, c, Token::None),
+            '\\' => set_transition(Token::String, c, Token::StringEscape),
+            _ => set_transition(Token::String, c, Token::String),
+        }
+        match c {
+            '\'' => set_transition(Token::Char, c, Token::None),
+            '\\' => set_transition(Token::Char, c, Token::CharEscape),
+            _ => set_transition(Token::Char, c, Token::Char),
+        }
+    }
+
+    for i in 0..alphabet_len {
+        let c = char::from_u32(i as u32).unwrap();
+        if c != '\n' {
+            set_transition(Token::LineComment, c, Token::LineComment)
         }
     }
 
@@ -350,6 +883,13 @@ fn create_transitions_table(alphabet_len: usize, num_states: usize) -> Vec<Vec<T
         set_transition(Token::Identifier, i, Token::Identifier);
     }
 
+    // Radix prefixes, hex digits, exponents and type suffixes are all part of
+    // a number. Which combinations are valid is checked by `number_literal`.
+    for i in ('a'..='z').chain('A'..='Z') {
+        set_transition(Token::Number, i, Token::Number);
+    }
+    set_transition(Token::Number, '_', Token::Number);
+
     for i in 'a'..='z' {
         set_transition(Token::None, i, Token::Identifier);
         set_transition(Token::Identifier, i, Token::Identifier);
@@ -386,6 +926,7 @@ fn create_transitions_table(alphabet_len: usize, num_states: usize) -> Vec<Vec<T
 
 fn next_position(position: Position, code: char) -> Position {
     let mut pos = position;
+    pos.offset += code.len_utf8();
     if code == '\n' {
         pos.row += 1;
         pos.col = 1;
@@ -404,6 +945,7 @@ fn next_position(position: Position, code: char) -> Position {
 
 fn prev_position(position: Position, prev_code: char) -> Position {
     let mut pos = position;
+    pos.offset -= prev_code.len_utf8();
     if prev_code == '\n' {
         pos.row -= 1;
         pos.col = 1;
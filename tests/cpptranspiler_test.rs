@@ -4,9 +4,9 @@ use rust::tokenizer;
 use std::io::Cursor;
 
 fn evaluate_and_compare(input: &str, expected_output: &str) -> bool {
-    match tokenizer::tokenize(Cursor::new(input)) {
+    match tokenizer::tokenize(Cursor::new(input)).collect::<Result<Vec<_>, _>>() {
         Ok(tokens) => match parser::parse(tokens) {
-            Some(ast) => {
+            Ok(ast) => {
                 let output = evaluator::cpptranspile(&ast);
                 println!("{:?}", ast.statements);
                 if output != expected_output {
@@ -16,7 +16,12 @@ fn evaluate_and_compare(input: &str, expected_output: &str) -> bool {
 
                 return true;
             }
-            None => return false,
+            Err(errors) => {
+                for error in errors {
+                    println!("{}", error);
+                }
+                return false;
+            }
         },
         Err(error) => {
             println!("{}", error);
@@ -47,6 +52,24 @@ fn function() {
         "#include <cstdint>
 int32_t main() { return 0; }"
     ));
+
+    assert!(evaluate_and_compare(
+        r#"fn add(a: i32, mut b: Array<u8>, s: String): f64 { return a }"#,
+        "#include <cstdint>
+
+#include <vector>
+
+#include <string>
+double add(const int32_t a, std::vector<uint8_t> b, const std::string s) { return a; }"
+    ));
+
+    assert!(evaluate_and_compare(
+        r#"fn main(args: Array<String>) { let n = 1 }"#,
+        "#include <string>
+
+#include <vector>
+int main(int argc, char** argv) { const std::vector<std::string> args(argv, argv + argc); const auto n = 1; return 0; }"
+    ));
 }
 
 #[test]
@@ -70,7 +93,7 @@ fn std_function_calls() {
         "#include <cstdint>
 
 #include <print>
-int32_t main() { std::println(\"hello\"); }"
+int32_t main() { std::println(\"hello\"); return 0; }"
     ));
 }
 
@@ -140,12 +163,26 @@ fn array() {
     ));
 
     assert!(evaluate_and_compare(
-            r#"
+        r#"
             let arr: Array<i32> = [1,2,3]
         "#,
-            "#include <cstdint>
-const int32_t arr[] = { 1, 2, 3 };"
-        ));
+        "#include <cstdint>
+
+#include <vector>
+const std::vector<int32_t> arr = { 1, 2, 3 };"
+    ));
+
+    assert!(evaluate_and_compare(
+        r#"fn sum(xs: Array<i32>): i32 { return xs[0] }
+fn main(): i32 {
+    let a: Array<i32> = [1, 2, 3]
+    return sum(a)
+}"#,
+        "#include <cstdint>
+
+#include <vector>
+int32_t sum(const std::vector<int32_t> xs) { return xs[0]; }int32_t main() { const std::vector<int32_t> a = { 1, 2, 3 }; return sum(a); }"
+    ));
 
     /*assert!(evaluate_and_compare(
         r#"
@@ -164,4 +201,278 @@ fn generics() {
         "#include <cstdint>
 const UserDefined<int32_t> i = 10;"
     ));
+
+    assert!(evaluate_and_compare(
+        r#"
+        let grid: Array<Array<i32>> = [[1], [2, 3]]
+        let pair: Pair<i32, Array<u8>> = p
+        "#,
+        "#include <cstdint>
+
+#include <vector>
+const std::vector<std::vector<int32_t>> grid = { { 1 }, { 2, 3 } };const Pair<int32_t, std::vector<uint8_t>> pair = p;"
+    ));
+}
+
+#[test]
+fn match_() {
+    assert!(evaluate_and_compare(
+        r#"
+    match i {
+        0 => { j = 1 }
+        -1 => j = 2
+    } else {
+        j = 3
+    }
+"#,
+        "switch ( i ) { case 0: { j = 1; } break; case -1: { j = 2; } break; default: { j = 3; } break; }"
+    ));
+
+    assert!(evaluate_and_compare(
+        r#"
+    match i {
+        0 => { j = 1 }
+        1..5 => { j = 2 }
+        n => { j = n }
+    }
+"#,
+        "if ( i == 0 ) { j = 1; } else if ( i >= 1 && i < 5 ) { j = 2; } else { const auto n = i; j = n; }"
+    ));
+
+    assert!(evaluate_and_compare(
+        r#"
+    match i {
+        1 => { j = 1 }
+        0x1 => { j = 2 }
+    }
+"#,
+        "if ( i == 1 ) { j = 1; } else if ( i == 0x1 ) { j = 2; }"
+    ));
+
+    assert!(evaluate_and_compare(
+        r#"
+    match i * 2 {
+        "a" => { j = 1 }
+    } else {
+        j = 3
+    }
+"#,
+        "{ const auto match_value = i * 2; if ( match_value == \"a\" ) { j = 1; } else { j = 3; } }"
+    ));
+}
+
+#[test]
+fn struct_() {
+    assert!(evaluate_and_compare(
+        r#"
+    struct Person {
+        age: u32
+        name: String
+    }
+"#,
+        "#include <cstdint>
+
+#include <string>
+struct Person { uint32_t age; std::string name; };"
+    ));
+
+    assert!(evaluate_and_compare(
+        r#"
+    struct Point {
+        x: i32
+        y: i32
+    }
+    let mut p = Point { y: 2, x: 1 }
+    p.x = p.y
+"#,
+        "#include <cstdint>
+struct Point { int32_t x; int32_t y; };auto p = Point{ .x = 1, .y = 2 };p.x = p.y;"
+    ));
+}
+
+#[test]
+fn string_literals() {
+    assert!(evaluate_and_compare(
+        r##"let s = "say \"hi\"\t\u{1}"
+let r = r#"C:\dir "x""#
+let c = '\''"##,
+        r#"const auto s = "say \"hi\"\t\001";const auto r = "C:\\dir \"x\"";const auto c = '\'';"#
+    ));
+}
+
+#[test]
+fn operators() {
+    assert!(evaluate_and_compare(
+        r#"if 0 == i % 2 && !(i >= 10) || i << 1 != 4 { i = i >> 1 }"#,
+        "if ( 0 == i % 2 && !(i >= 10) || i << 1 != 4 ) { i = i >> 1; } "
+    ));
+}
+
+#[test]
+fn precedence() {
+    assert!(evaluate_and_compare("x = a + b == c", "x = a + b == c;"));
+    assert!(evaluate_and_compare("x = a - (b - c) * d", "x = a - (b - c) * d;"));
+    assert!(evaluate_and_compare("x = -(a + b)", "x = -(a + b);"));
+    assert!(evaluate_and_compare("x = y = 1 << 2 + 3", "x = y = 1 << 2 + 3;"));
+}
+
+#[test]
+fn booleans() {
+    assert!(evaluate_and_compare(
+        "let b: bool = true\nwhile !b || false { b = false }",
+        "const bool b = true;while ( !b || false ) { b = false; }"
+    ));
+}
+
+#[test]
+fn compound_assignments() {
+    assert!(evaluate_and_compare(
+        "i += 1\narr[i] *= a + b\np.x <<= y -= 2",
+        "i += 1;arr[i] *= a + b;p.x <<= y -= 2;"
+    ));
+}
+
+#[test]
+fn header_and_source() {
+    let tokens = tokenizer::tokenize(Cursor::new(
+        r#"
+struct Point {
+    x: i32
+}
+fn norm(p: Point): i32 { return p.x }
+fn log(p: Point) { norm(p) }
+fn twice(x: f32) { return x * 2 }
+fn main(): i32 { return 0 }
+"#,
+    ))
+    .collect::<Result<Vec<_>, _>>()
+    .unwrap();
+    let ast = parser::parse(tokens).unwrap();
+    assert!(evaluator::check(&ast).is_empty());
+    let (module, diagnostics) = evaluator::cpptranspile_module(&ast, "geometry-2d", &[]);
+    assert!(diagnostics.is_empty());
+    assert_eq!(
+        module.header,
+        "#ifndef GEOMETRY_2D_H
+#define GEOMETRY_2D_H
+#include <cstdint>
+struct Point { int32_t x; };
+int32_t norm(const Point p);
+void log(const Point p);
+float twice(const float x);
+#endif
+"
+    );
+    assert_eq!(
+        module.source,
+        "#include \"geometry-2d.h\"
+#include <cstdint>
+int32_t norm(const Point p) { return p.x; }void log(const Point p) { norm(p); }float twice(const float x) { return x * 2; }int32_t main() { return 0; }"
+    );
+}
+
+#[test]
+fn namespaces() {
+    assert!(evaluate_and_compare(
+        r#"namespace A::B
+fn twice(t: i32): i32 { return t * 2 }
+fn main(): i32 { return twice(C::D::f(1)) }
+let x = 1"#,
+        "#include <cstdint>
+namespace A::B { int32_t twice(const int32_t t) { return t * 2; }const auto x = 1; }int32_t main() { using namespace A::B; return twice(C::D::f(1)); }"
+    ));
+
+    let parse = |input: &str| {
+        let tokens = tokenizer::tokenize(Cursor::new(input))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        parser::parse(tokens).unwrap()
+    };
+    let geometry = parse("namespace Geo\nstruct Point {\n x: i32\n}\nfn origin(): Point { return Point { x: 0 } }");
+    let app = parse("fn main(): i32 {\nlet p: Geo::Point = Geo::origin()\nreturn p.x\n}");
+    let modules = [
+        evaluator::ModuleSymbols::collect(&geometry, "geometry"),
+        evaluator::ModuleSymbols::collect(&app, "main"),
+    ];
+    let (module, _) = evaluator::cpptranspile_module(&geometry, "geometry", &modules);
+    assert_eq!(
+        module.header,
+        "#ifndef GEOMETRY_H
+#define GEOMETRY_H
+#include <cstdint>
+namespace Geo {
+struct Point { int32_t x; };
+Point origin();
+}
+#endif
+"
+    );
+    let (module, _) = evaluator::cpptranspile_module(&app, "main", &modules);
+    assert_eq!(
+        module.source,
+        "#include \"main.h\"
+#include \"geometry.h\"
+#include <cstdint>
+int32_t main() { const Geo::Point p = Geo::origin(); return p.x; }"
+    );
+}
+
+#[test]
+fn generic_functions() {
+    assert!(evaluate_and_compare(
+        "fn pick<T, U>(t: T, u: U): T { return t }\nlet a = pick::<i32, Array<u8>>(1, [2])",
+        "#include <cstdint>
+
+#include <vector>
+template<typename T, typename U> T pick(const T t, const U u) { return t; }const auto a = pick<int32_t, std::vector<uint8_t>>(1, { 2 });"
+    ));
+
+    let tokens = tokenizer::tokenize(Cursor::new(
+        "fn id<T>(t: T): T { return t }\nfn main(): i32 { return id(0) }",
+    ))
+    .collect::<Result<Vec<_>, _>>()
+    .unwrap();
+    let ast = parser::parse(tokens).unwrap();
+    let (module, _) = evaluator::cpptranspile_module(&ast, "util", &[]);
+    assert_eq!(
+        module.header,
+        "#ifndef UTIL_H
+#define UTIL_H
+template<typename T> T id(const T t) { return t; }
+#endif
+"
+    );
+    assert_eq!(
+        module.source,
+        "#include \"util.h\"
+#include <cstdint>
+int32_t main() { return id(0); }"
+    );
+}
+
+#[test]
+fn closures() {
+    assert!(evaluate_and_compare(
+        "let even = fn (i: i32): bool {\n    return 0 == i % 2\n}",
+        "#include <cstdint>
+const auto even = [](const int32_t i) -> bool { return 0 == i % 2; };"
+    ));
+
+    assert!(evaluate_and_compare(
+        r#"fn apply(f: fn(i32): bool, x: i32): bool { return f(x) }
+fn count(limit: i32): i32 {
+    let mut total = 0
+    let step = 2
+    let add = fn (x: i32) {
+        total += x * step
+        let inner = fn () { return total + limit }
+    }
+    apply(fn (x: i32): bool { return x < limit }, 1)
+    return total
+}"#,
+        "#include <functional>
+
+#include <cstdint>
+bool apply(const std::function<bool(int32_t)> f, const int32_t x) { return f(x); }int32_t count(const int32_t limit) { auto total = 0; const auto step = 2; const auto add = [&total, step, limit](const int32_t x) { total += x * step; const auto inner = [&total, limit]() { return total + limit; }; }; apply([limit](const int32_t x) -> bool { return x < limit; }, 1); return total; }"
+    ));
 }
@@ -0,0 +1,80 @@
+use rust::diagnostics::{Diagnostic, Span};
+use rust::parser;
+use rust::tokenizer;
+use rust::tokenizer::Position;
+use std::io::Cursor;
+
+fn render_first_error(input: &str) -> String {
+    match tokenizer::tokenize(Cursor::new(input)).collect::<Result<Vec<_>, _>>() {
+        Ok(tokens) => match parser::parse(tokens) {
+            Ok(_) => String::new(),
+            Err(errors) => Diagnostic::from(&errors[0]).render("main.cpp2", input),
+        },
+        Err(error) => Diagnostic::from(&error).render("main.cpp2", input),
+    }
+}
+
+#[test]
+fn parser_error() {
+    assert_eq!(
+        render_first_error("let i = 10\nlet = 10"),
+        "error: expected Identifier, found `=`
+ --> main.cpp2:2:5
+  |
+2 | let = 10
+  |     ^ expected Identifier
+"
+    );
+}
+
+#[test]
+fn tokenizer_error() {
+    assert_eq!(
+        render_first_error("let i = \"unclosed"),
+        "error: unclosed string
+ --> main.cpp2:1:9
+  |
+1 | let i = \"unclosed
+  |         ^ string starts here
+  = help: add a closing `\"`
+"
+    );
+}
+
+#[test]
+fn multi_token_span() {
+    let source = "let i = 10\n\tstd::foo(i)";
+    let diagnostic = Diagnostic::warning(
+        "unknown standard library function `std::foo`",
+        Span::new(Position::new(2, 5, 12), Position::new(2, 13, 20)),
+    )
+    .with_label("no header is known for this function")
+    .with_secondary(
+        Span::new(Position::new(1, 5, 4), Position::new(1, 6, 5)),
+        "argument declared here",
+    )
+    .with_note("std functions are looked up in functions.csv");
+
+    assert_eq!(
+        diagnostic.render("main.cpp2", source),
+        "warning: unknown standard library function `std::foo`
+ --> main.cpp2:2:5
+  |
+1 | let i = 10
+  |     - argument declared here
+2 |     std::foo(i)
+  |     ^^^^^^^^ no header is known for this function
+  = note: std functions are looked up in functions.csv
+"
+    );
+}
+
+#[test]
+fn token_end_position() {
+    let tokens = tokenizer::tokenize(Cursor::new("let name = \"abc\""))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(tokens[1].start_position, Position::new(1, 5, 4));
+    assert_eq!(tokens[1].end_position, Position::new(1, 9, 8));
+    assert_eq!(tokens[3].end_position, Position::new(1, 17, 16));
+}
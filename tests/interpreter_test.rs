@@ -4,9 +4,9 @@ use rust::tokenizer;
 use std::io::Cursor;
 
 fn evaluate_and_compare(input: &str, expected_output: &str) -> bool {
-    match tokenizer::tokenize(Cursor::new(input)) {
+    match tokenizer::tokenize(Cursor::new(input)).collect::<Result<Vec<_>, _>>() {
         Ok(tokens) => match parser::parse(tokens) {
-            Some(ast) => {
+            Ok(ast) => {
                 let output = evaluator::interpret(&ast);
                 println!("{:?}", ast.statements);
                 if output != expected_output {
@@ -16,7 +16,12 @@ fn evaluate_and_compare(input: &str, expected_output: &str) -> bool {
 
                 return true;
             }
-            None => return false,
+            Err(errors) => {
+                for error in errors {
+                    println!("{}", error);
+                }
+                return false;
+            }
         },
         Err(error) => {
             println!("{}", error);
@@ -95,7 +100,6 @@ fn for_() {
     ));
 }
 
-
 #[test]
 fn sort_() {
     assert!(evaluate_and_compare(
@@ -126,7 +130,6 @@ return arr
     ));
 }
 
-
 #[test]
 fn if_() {
     assert!(evaluate_and_compare(
@@ -149,3 +152,411 @@ fn if_() {
         "11"
     ));
 }
+
+#[test]
+fn match_() {
+    assert!(evaluate_and_compare(
+        r#"
+        let i = 3
+        let j = 0
+        match i {
+            0 => { j = 1 }
+            1..5 => { j = 2 }
+        } else {
+            j = 3
+        }
+        return j
+"#,
+        "2"
+    ));
+
+    assert!(evaluate_and_compare(
+        r#"
+        let i = 7
+        let j = 0
+        match i {
+            0 => j = 1
+            n => j = n * 2
+        }
+        return j
+"#,
+        "14"
+    ));
+
+    assert!(evaluate_and_compare(
+        r#"
+        let i = 9
+        let j = 0
+        match i {
+            0 => j = 1
+        } else {
+            j = 3
+        }
+        return j
+"#,
+        "3"
+    ));
+}
+
+#[test]
+fn struct_() {
+    assert!(evaluate_and_compare(
+        r#"
+        struct Point {
+            x: i32
+            y: i32
+        }
+        let mut p = Point { y: 2, x: 1 }
+        p.x = p.y + 10
+        return p
+"#,
+        "Point { x: 12, y: 2 }"
+    ));
+
+    assert!(evaluate_and_compare(
+        r#"
+        struct Inner {
+            value: i32
+        }
+        struct Outer {
+            inner: Inner
+        }
+        let mut o = Outer { inner: Inner { value: 1 } }
+        o.inner.value = 5
+        return o.inner.value
+"#,
+        "5"
+    ));
+
+    assert!(evaluate_and_compare(
+        r#"
+        struct Point {
+            x: i32
+        }
+        let mut arr = [Point { x: 1 }, Point { x: 2 }]
+        let i = 1
+        arr[i].x = 5
+        arr[0].x += arr[i].x
+        return arr
+"#,
+        "[Point { x: 6 }, Point { x: 5 }]"
+    ));
+}
+
+#[test]
+fn functions() {
+    assert!(evaluate_and_compare(
+        r#"
+fn fib(n: i32): i32 {
+    if n < 2 {
+        return n
+    }
+    return fib(n - 1) + fib(n - 2)
+}
+
+fn main(): i32 {
+    return fib(10)
+}
+"#,
+        "55"
+    ));
+
+    assert!(evaluate_and_compare(
+        r#"
+let limit = 3
+
+fn find(arr: Array<i32>, value: i32): i32 {
+    for i in 0..limit {
+        let mut j = 0
+        while j < 10 {
+            let current = arr[i]
+            if current == value {
+                return i
+            }
+            j = j + 10
+        }
+    }
+    return -1
+}
+
+fn main(): i32 {
+    let arr = [4, 8, 15]
+    return find(arr, 8) * 10 + find(arr, 16)
+}
+"#,
+        "9"
+    ));
+}
+
+#[test]
+fn sort_3_example() {
+    assert!(evaluate_and_compare(
+        include_str!("../examples/sort_3.cpp2"),
+        "0"
+    ));
+}
+
+#[test]
+fn exit_code() {
+    let run = |input: &str| {
+        let tokens = tokenizer::tokenize(Cursor::new(input))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        evaluator::run(&parser::parse(tokens).unwrap())
+    };
+    assert_eq!(run("fn main(): i32 { return 3 }"), 3);
+    assert_eq!(run("fn main(): i32 { std::println(\"hi\") }"), 0);
+    assert_eq!(run("let i = 4\nreturn i + 1"), 5);
+}
+
+#[test]
+fn string_literals() {
+    assert!(evaluate_and_compare(r#"let s = "a\tb\u{21}""#, "a\tb!"));
+}
+
+#[test]
+fn number_literals() {
+    assert!(evaluate_and_compare("let x = 1.5 * 2 + 0x10", "19"));
+    assert!(evaluate_and_compare(
+        "let x = 0.1f32",
+        "0.10000000149011612"
+    ));
+    assert!(evaluate_and_compare("let x = 1_000u64 / 8", "125"));
+    assert!(evaluate_and_compare("let x = 4294967295u32 + 1u32", "0"));
+    assert!(evaluate_and_compare("let x = 200u8 * 2", "144"));
+    assert!(evaluate_and_compare("let x = -128i8 - 1i8", "127"));
+}
+
+#[test]
+fn operators() {
+    assert!(evaluate_and_compare("let x = 17 % 5 + (1 << 4) + (64 >> 2)", "34"));
+    assert!(evaluate_and_compare(
+        r#"
+        let x = 0
+        if 2 <= 2 { x = x + 1 }
+        if 1 >= 2 { x = x + 10 }
+        if 1 != 2 { x = x + 100 }
+        if !(3 < 1) { x = x + 1000 }
+        return x
+"#,
+        "1101"
+    ));
+    assert!(evaluate_and_compare("let x = 7.5 % 2", "1.5"));
+    // The right side would divide by zero if it were evaluated.
+    assert!(evaluate_and_compare(
+        r#"
+        let zero = 0
+        let x = 0
+        if 1 > 2 && 1 / zero == 0 { x = 1 }
+        if 1 < 2 || 1 / zero == 0 { x = x + 10 }
+        return x
+"#,
+        "10"
+    ));
+}
+
+#[test]
+fn precedence() {
+    assert!(evaluate_and_compare("let x = 10 - 4 - 3", "3"));
+    assert!(evaluate_and_compare("let x = 1 + 2 * 3 << 1", "14"));
+    assert!(evaluate_and_compare(
+        r#"
+        let a = 1
+        let b = 2
+        let c = 0
+        if a + b == 3 { c = 1 }
+        return c
+"#,
+        "1"
+    ));
+    assert!(evaluate_and_compare(
+        r#"
+        let a = 0
+        let b = 0
+        a = b = 5 - 1
+        return a + b
+"#,
+        "8"
+    ));
+}
+
+#[test]
+fn booleans() {
+    assert!(evaluate_and_compare("let b = 1 < 2 && !false", "true"));
+    assert!(evaluate_and_compare("let b = (1 == 2) == false", "true"));
+    assert!(evaluate_and_compare(
+        r#"
+        let done = false
+        let i = 0
+        while !done {
+            i = i + 1
+            done = i == 3
+        }
+        if done { i = i * 10 }
+        return i
+"#,
+        "30"
+    ));
+    assert!(evaluate_and_compare(
+        r#"
+        let b = true
+        let x = 0
+        match b {
+            false => x = 1
+            true => x = 2
+        }
+        return x
+"#,
+        "2"
+    ));
+}
+
+#[test]
+fn string_comparisons() {
+    assert!(evaluate_and_compare(
+        r#"
+        let s = "abc"
+        let x = 0
+        if s == "abc" { x = x + 1 }
+        if s != "abd" { x = x + 10 }
+        if s < "abd" && "b" >= s { x = x + 100 }
+        return x
+"#,
+        "111"
+    ));
+    assert!(evaluate_and_compare(
+        r#"
+        let c = 'a'
+        let x = 0
+        if c == 'a' { x = x + 1 }
+        if c != 'b' { x = x + 10 }
+        return x
+"#,
+        "11"
+    ));
+}
+
+#[test]
+fn statement_termination() {
+    assert!(evaluate_and_compare("let a = 10\nlet b = a\n-1\nreturn b", "10"));
+    assert!(evaluate_and_compare("let a = 10; let b = a -\n1; return b", "9"));
+}
+
+#[test]
+fn compound_assignments() {
+    assert!(evaluate_and_compare(
+        "let mut x = 5\nx += 10\nx *= 2\nx <<= 1\nx %= 7\nreturn x",
+        "4"
+    ));
+    assert!(evaluate_and_compare(
+        "let mut arr = [1, 2, 3]\narr[1] *= 3 + arr[2]\nreturn arr[1]",
+        "12"
+    ));
+    assert!(evaluate_and_compare(
+        r#"
+        struct Point {
+            x: i32
+            y: i32
+        }
+        let mut p = Point { x: 1, y: 2 }
+        p.x -= p.y
+        return p
+"#,
+        "Point { x: -1, y: 2 }"
+    ));
+}
+
+#[test]
+fn main_function() {
+    assert!(evaluate_and_compare("fn main(): i32 { let a = 1 }", "0"));
+    assert!(evaluate_and_compare(
+        "fn main(args: Array<String>): i32 { return 3 }",
+        "3"
+    ));
+}
+
+#[test]
+fn namespaces() {
+    assert!(evaluate_and_compare(
+        "namespace A::B\nfn twice(x: i32): i32 { return x * 2 }\nfn main(): i32 { return A::B::twice(4) + twice(1) }",
+        "10"
+    ));
+}
+
+#[test]
+fn generic_functions() {
+    assert!(evaluate_and_compare(
+        "fn pick<T>(first: bool, a: T, b: T): T { if first { return a }\nreturn b }\nfn main(): i32 { return pick::<i32>(false, 1, 2) + pick(true, 3, 4) }",
+        "5"
+    ));
+}
+
+#[test]
+fn closures() {
+    assert!(evaluate_and_compare(
+        r#"fn apply(f: fn(i32): i32, x: i32): i32 { return f(x) }
+fn double(x: i32): i32 { return x * 2 }
+fn main(): i32 {
+    let mut total = 0
+    let offset = 10
+    let add = fn (x: i32) { total += x + offset }
+    add(1)
+    add(2)
+    let shift = fn (x: i32): i32 { return x + total }
+    total = 100
+    return apply(shift, 1) + apply(double, 3)
+}"#,
+        "107"
+    ));
+
+    assert!(evaluate_and_compare(
+        r#"fn counter(): fn(): i32 {
+    let mut count = 0
+    return fn (): i32 {
+        count += 1
+        return count
+    }
+}
+let next = counter()
+next()
+next()
+next()"#,
+        "3"
+    ));
+}
+
+#[test]
+fn match_binding_scope() {
+    assert!(evaluate_and_compare(
+        r#"
+        let n = 5
+        let mut j = 0
+        match 7 {
+            0 => { j = 1 }
+            n => { j = n }
+        }
+        return n + j - 7
+"#,
+        "5"
+    ));
+}
+
+#[test]
+fn block_scope() {
+    assert!(evaluate_and_compare(
+        r#"
+        let mut x = 1
+        let mut y = 0
+        if true {
+            let x = 2
+            y = x
+        }
+        for i in [3, 4] {
+            let x = i
+            y = y + x
+        }
+        return x * 10 + y
+"#,
+        "19"
+    ));
+}
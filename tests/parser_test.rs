@@ -1,12 +1,19 @@
 use rust::parser;
+use rust::parser::{ASTExpressionKind, ASTStatementKind};
 use rust::tokenizer;
+use rust::tokenizer::{Position, Token};
 use std::io::Cursor;
 
 fn is_parsable(input: &str) -> bool {
-    match tokenizer::tokenize(Cursor::new(input)) {
+    match tokenizer::tokenize(Cursor::new(input)).collect::<Result<Vec<_>, _>>() {
         Ok(tokens) => match parser::parse(tokens) {
-            Some(_) => return true,
-            None => return false,
+            Ok(_) => return true,
+            Err(errors) => {
+                for error in errors {
+                    println!("{}", error);
+                }
+                return false;
+            }
         },
         Err(error) => {
             println!("{}", error);
@@ -107,10 +114,16 @@ fn array() {
     return a[i]
 "#
     ));
+
+    assert!(is_parsable(
+        "let grid: Array<Array<i32>> = [[1], [2, 3]]\nlet deep: Array<Array<Array<u8>>> = []"
+    ));
+    let errors = parse_errors("let a: Array<i32");
+    assert_eq!(errors[0].expected, vec![Token::Comma, Token::GreaterThan]);
+    assert_eq!(errors[0].found.token, Token::EOF);
 }
 
 #[test]
-#[ignore]
 fn match_() {
     assert!(is_parsable(
         r#"
@@ -125,12 +138,23 @@ fn match_() {
     	i => 1 == 3
     	i => 2 == 3
     }
+"#
+    ));
+    assert!(is_parsable(
+        r#"
+    match i {
+        0 => { i = 1 }
+        1..5 => { i = 2 }
+        -1 => { i = 3 }
+        _ => { i = 4 }
+    } else {
+        i = 5
+    }
 "#
     ));
 }
 
 #[test]
-#[ignore]
 fn struct_() {
     assert!(is_parsable(
         r#"
@@ -146,4 +170,271 @@ fn struct_() {
     }
 "#
     ));
+    assert!(is_parsable(
+        r#"
+    struct Point {
+        x: i32
+        y: i32
+    }
+    let mut p = Point { x: 1, y: 2 }
+    p.x = p.y + 1
+    let empty = Point {}
+"#
+    ));
+}
+
+fn parse_errors(input: &str) -> Vec<parser::ParseError> {
+    let tokens = tokenizer::tokenize(Cursor::new(input))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    match parser::parse(tokens) {
+        Ok(_) => Vec::new(),
+        Err(errors) => errors,
+    }
+}
+
+#[test]
+fn errors() {
+    let errors = parse_errors("let = 10");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].expected, vec![Token::Identifier]);
+    assert_eq!(errors[0].found.token, Token::Assignment);
+    assert_eq!(errors[0].position, Position::new(1, 5, 4));
+
+    let errors = parse_errors(
+        r#"
+let i = )
+let j = 10
+fn main(): i32 {
+    let k = 10 +
+    let m: = 3
+    return k
+}
+let n = ]
+"#,
+    );
+    let positions: Vec<(u32, u32)> = errors
+        .iter()
+        .map(|error| (error.position.row, error.position.col))
+        .collect();
+    assert_eq!(positions, vec![(2, 9), (6, 5), (6, 12), (9, 9)]);
+    assert_eq!(errors[0].found.token, Token::RightParantheses);
+    assert!(errors[0].expected.contains(&Token::Number));
+}
+
+/// Renders the first statement of `input`, an expression, with every
+/// operator application in parentheses.
+fn grouping(input: &str) -> String {
+    fn render(expression: &parser::ASTExpression) -> String {
+        match &expression.kind {
+            ASTExpressionKind::Binary(binary) => format!(
+                "({} {} {})",
+                render(&binary.left),
+                binary.operator.token.lexeme,
+                render(&binary.right)
+            ),
+            ASTExpressionKind::Unary(unary) => {
+                format!("({}{})", unary.operator.token.lexeme, render(&unary.operand))
+            }
+            ASTExpressionKind::Assignment(assignment) => format!(
+                "({} = {})",
+                assignment.identifier.lexeme,
+                render(&assignment.expression)
+            ),
+            ASTExpressionKind::ArrayAssignment(assignment) => format!(
+                "({} = {})",
+                render(&assignment.index_expression),
+                render(&assignment.expression)
+            ),
+            ASTExpressionKind::CompoundAssignment(assignment) => format!(
+                "({} {} {})",
+                render(&assignment.target),
+                assignment.operator.token.lexeme,
+                render(&assignment.expression)
+            ),
+            ASTExpressionKind::FieldAccess(field_access) => {
+                format!("{}.{}", render(&field_access.object), field_access.field.lexeme)
+            }
+            ASTExpressionKind::ArrayIndex(array_index) => {
+                format!("{}[{}]", render(&array_index.array), render(&array_index.index))
+            }
+            ASTExpressionKind::Parenthesized(parenthesized) => render(&parenthesized.expression),
+            ASTExpressionKind::Variable(variable) => variable.identifier.lexeme.clone(),
+            ASTExpressionKind::Number(number) => number.num.lexeme.clone(),
+            kind => panic!("unexpected expression {:?}", kind),
+        }
+    }
+    let tokens = tokenizer::tokenize(Cursor::new(input))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let ast = parser::parse(tokens).unwrap();
+    match &ast.statements[0].kind {
+        ASTStatementKind::Expression(expression) => render(expression),
+        kind => panic!("unexpected statement {:?}", kind),
+    }
+}
+
+#[test]
+fn precedence() {
+    assert_eq!(grouping("a + b == c"), "((a + b) == c)");
+    assert_eq!(grouping("a - b - c"), "((a - b) - c)");
+    assert_eq!(grouping("a / b * c % d"), "(((a / b) * c) % d)");
+    assert_eq!(grouping("a || b && c | d ^ e & f"), "(a || (b && (c | (d ^ (e & f)))))");
+    assert_eq!(grouping("a << 1 < b + 1"), "((a << 1) < (b + 1))");
+    assert_eq!(grouping("-a * ~b"), "((-a) * (~b))");
+    assert_eq!(grouping("a = b = c + 1"), "(a = (b = (c + 1)))");
+    assert_eq!(grouping("arr[i] = (a - b) - c"), "(arr[i] = ((a - b) - c))");
+
+    let errors = parse_errors("a + b = c");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].found.token, Token::Assignment);
+}
+
+#[test]
+fn compound_assignment() {
+    assert_eq!(grouping("a += b * 2"), "(a += (b * 2))");
+    assert_eq!(grouping("arr[i] <<= 1"), "(arr[i] <<= 1)");
+    assert_eq!(grouping("p.x -= a = b"), "(p.x -= (a = b))");
+
+    let errors = parse_errors("a + b += c");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].found.token, Token::AdditionAssignment);
+}
+
+fn parse_warnings(input: &str) -> (usize, Vec<String>) {
+    let tokens = tokenizer::tokenize(Cursor::new(input))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let ast = parser::parse(tokens).unwrap();
+    let warnings = ast.warnings.iter().map(|warning| warning.to_string()).collect();
+    (ast.statements.len(), warnings)
+}
+
+#[test]
+fn statement_termination() {
+    assert_eq!(parse_warnings("let a = 1; let b = 2;\nb = a"), (3, vec![]));
+    assert_eq!(parse_warnings("let a = b +\n1\nlet c = a\n* 2"), (2, vec![]));
+    assert_eq!(parse_warnings("let a = f(1,\n2) + (b\n- 3)"), (1, vec![]));
+    assert_eq!(parse_warnings("if a < b { a = b; b = a }"), (1, vec![]));
+    assert_eq!(
+        parse_warnings("let x = a\n-1\nlet y = x\n[1, 2]"),
+        (
+            4,
+            vec![
+                String::from("warning: `-` at the start of a line begins a new statement at 2:1"),
+                String::from("warning: `[` at the start of a line begins a new statement at 4:1"),
+            ]
+        )
+    );
+    assert_eq!(parse_warnings("let x = a;\n-1").1, Vec::<String>::new());
+    assert_eq!(
+        parse_warnings("let x = a * b << c\n-1").1,
+        vec![String::from(
+            "warning: `-` at the start of a line begins a new statement at 2:1"
+        )]
+    );
+
+    let errors = parse_errors("let a = 1 let b = 2");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].expected, vec![Token::Semicolon]);
+    assert_eq!(errors[0].position, Position::new(1, 11, 10));
+}
+
+#[test]
+fn namespaces() {
+    let tokens = tokenizer::tokenize(Cursor::new(
+        "namespace A::B\nfn f(p: C::Point): i32 { return C::D::g(p, 1) }",
+    ))
+    .collect::<Result<Vec<_>, _>>()
+    .unwrap();
+    let ast = parser::parse(tokens).unwrap();
+    assert_eq!(ast.namespace().unwrap().name(), "A::B");
+    let func_decl = match &ast.statements[1].kind {
+        ASTStatementKind::FuncDecl(func_decl) => func_decl,
+        kind => panic!("unexpected statement {:?}", kind),
+    };
+    match &func_decl.parameters[0].type_annotation.as_ref().unwrap().kind {
+        ASTExpressionKind::TypeAnnotation(type_annotation) => {
+            assert_eq!(parser::path_name(&type_annotation.qualifier), "C");
+            assert_eq!(type_annotation.base.lexeme, "Point");
+        }
+        kind => panic!("unexpected expression {:?}", kind),
+    }
+
+    let errors = parse_errors("A::B::value + 1");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].expected, vec![Token::LeftParantheses]);
+}
+
+#[test]
+fn generic_functions() {
+    let tokens = tokenizer::tokenize(Cursor::new(
+        "fn pick<T, U>(t: T, u: U): T { return t }\nlet a = pick::<i32, String>(1, \"2\")",
+    ))
+    .collect::<Result<Vec<_>, _>>()
+    .unwrap();
+    let ast = parser::parse(tokens).unwrap();
+    match &ast.statements[0].kind {
+        ASTStatementKind::FuncDecl(func_decl) => {
+            let generics: Vec<&str> = func_decl
+                .generics
+                .iter()
+                .map(|generic| generic.lexeme.as_str())
+                .collect();
+            assert_eq!(generics, vec!["T", "U"]);
+        }
+        kind => panic!("unexpected statement {:?}", kind),
+    }
+    match &ast.statements[1].kind {
+        ASTStatementKind::Let(let_statement) => match &let_statement.initializer.kind {
+            ASTExpressionKind::Call(call) => {
+                assert_eq!(call.identifier.lexeme, "pick");
+                assert_eq!(call.generic_arguments.len(), 2);
+                assert_eq!(call.arguments.len(), 2);
+            }
+            kind => panic!("unexpected expression {:?}", kind),
+        },
+        kind => panic!("unexpected statement {:?}", kind),
+    }
+
+    assert!(is_parsable("Math::max::<f64>(a, b)"));
+    let errors = parse_errors("fn id<T, 1>(t: T): T { return t }");
+    assert_eq!(errors[0].expected, vec![Token::Identifier]);
+}
+
+#[test]
+fn closures() {
+    let tokens = tokenizer::tokenize(Cursor::new(
+        "fn apply(f: fn(i32, String): bool, x: i32): bool { return f(x, \"\") }\nlet even = fn (i: i32): bool {\n    return 0 == i % 2\n}\nlet log = fn () { }",
+    ))
+    .collect::<Result<Vec<_>, _>>()
+    .unwrap();
+    let ast = parser::parse(tokens).unwrap();
+    match &ast.statements[0].kind {
+        ASTStatementKind::FuncDecl(func_decl) => {
+            match &func_decl.parameters[0].type_annotation.as_ref().unwrap().kind {
+                ASTExpressionKind::FunctionType(function_type) => {
+                    assert_eq!(function_type.parameters.len(), 2);
+                    assert!(function_type.return_type.is_some());
+                }
+                kind => panic!("unexpected expression {:?}", kind),
+            }
+        }
+        kind => panic!("unexpected statement {:?}", kind),
+    }
+    for statement in &ast.statements[1..] {
+        match &statement.kind {
+            ASTStatementKind::Let(let_statement) => {
+                assert!(matches!(
+                    let_statement.initializer.kind,
+                    ASTExpressionKind::Closure(_)
+                ));
+            }
+            kind => panic!("unexpected statement {:?}", kind),
+        }
+    }
+
+    assert!(is_parsable("let sorted = sort(items, fn (a: i32, b: i32): bool { return a < b })"));
+    let errors = parse_errors("let f = fn { return 1 }");
+    assert_eq!(errors[0].expected, vec![Token::LeftParantheses]);
 }
@@ -0,0 +1,47 @@
+use rust::evaluator::Session;
+use rust::repl;
+
+#[test]
+fn multi_line_input() {
+    assert!(repl::is_complete("let i = 10"));
+    assert!(!repl::is_complete("fn main(): i32 {"));
+    assert!(!repl::is_complete(
+        "fn main(): i32 {\n    if i > 2 {\n    }"
+    ));
+    assert!(repl::is_complete("fn main(): i32 {\n    return 0\n}"));
+    assert!(repl::is_complete(r#"std::println("{")"#));
+    assert!(repl::is_complete("let c = '{'"));
+    assert!(repl::is_complete("let c = '\\''"));
+    assert!(repl::is_complete("let i = 1 // {"));
+    assert!(repl::is_complete("let i = /* { /* ( */ [ */ 1"));
+    assert!(!repl::is_complete("let i = /* } */ {"));
+}
+
+#[test]
+fn session() {
+    let mut session = Session::new();
+    assert_eq!(repl::evaluate(&mut session, "let mut i = 2"), "");
+    assert_eq!(repl::evaluate(&mut session, "i = i * 5"), "10");
+    assert_eq!(
+        repl::evaluate(&mut session, "fn inc(x: i32): i32 {\n    return x + 1\n}"),
+        ""
+    );
+    assert_eq!(repl::evaluate(&mut session, "inc(i)"), "11");
+    assert_eq!(repl::evaluate(&mut session, ":type inc(i)"), "i32");
+    assert_eq!(
+        repl::evaluate(&mut session, ":type [\"a\"]"),
+        "Array<String>"
+    );
+    assert_eq!(
+        repl::evaluate(&mut session, ":cpp let j = i + 1"),
+        "#include <cstdint>\nconst int32_t j = i + 1;"
+    );
+    assert!(repl::evaluate(&mut session, "undefined")
+        .starts_with("error: cannot find value `undefined` in this scope"));
+    assert!(repl::evaluate(&mut session, "let k = 1\nk = 2")
+        .starts_with("error: cannot assign to immutable variable `k`"));
+    assert_eq!(repl::evaluate(&mut session, "i"), "10");
+
+    repl::evaluate(&mut session, ":reset");
+    assert!(repl::evaluate(&mut session, "i").starts_with("error"));
+}
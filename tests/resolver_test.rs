@@ -0,0 +1,284 @@
+use rust::evaluator;
+use rust::parser;
+use rust::tokenizer;
+use std::io::Cursor;
+
+fn resolve(input: &str) -> Vec<String> {
+    let tokens = tokenizer::tokenize(Cursor::new(input))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    match parser::parse(tokens) {
+        Ok(ast) => evaluator::resolve(&ast)
+            .iter()
+            .map(|diagnostic| diagnostic.to_string())
+            .collect(),
+        Err(errors) => {
+            for error in errors {
+                println!("{}", error);
+            }
+            panic!("failed to parse");
+        }
+    }
+}
+
+#[test]
+fn undefined_variables() {
+    assert_eq!(
+        resolve("let i: u32 = u - 10 * (i)"),
+        vec![
+            "error: cannot find value `u` in this scope at 1:14",
+            "error: cannot use `i` before its declaration at 1:24",
+        ]
+    );
+    assert_eq!(
+        resolve(
+            r#"
+fn main(): i32 {
+    if 1 > 0 {
+        let a = 1
+    }
+    b = a
+}
+"#
+        ),
+        vec![
+            "error: cannot find value `b` in this scope at 6:5",
+            "error: cannot find value `a` in this scope at 6:9",
+        ]
+    );
+}
+
+#[test]
+fn use_before_declaration() {
+    assert_eq!(
+        resolve(
+            r#"
+fn main(): i32 {
+    {
+        let b = a + 1
+    }
+    let a = 2
+}
+"#
+        ),
+        vec!["error: cannot use `a` before its declaration at 4:17"]
+    );
+}
+
+#[test]
+fn scopes() {
+    assert!(resolve(
+        r#"
+fn main(): i32 {
+    let mut a : i32 = 3
+    if a > 1 {
+        let temp : i32 = a
+        a = temp
+    }
+    if a > 2 {
+        let temp : i32 = a
+    }
+    for i in 0..10 {
+        a = a + i
+    }
+    match a {
+        0 => { a = 1 }
+        n => { a = n + 1 }
+    }
+    return helper(a)
+}
+
+fn helper(x: i32): i32 {
+    return x
+}
+"#
+    )
+    .is_empty());
+}
+
+#[test]
+fn shadowing() {
+    assert_eq!(
+        resolve(
+            r#"
+fn main(): i32 {
+    let a = 1
+    for a in 0..10 {
+        let a = 2
+    }
+}
+"#
+        ),
+        vec![
+            "warning: `a` shadows a variable from an outer scope at 4:9",
+            "warning: `a` shadows a variable from an outer scope at 5:13",
+        ]
+    );
+    assert_eq!(
+        resolve(
+            r#"
+fn main(x: i32): i32 {
+    let x = 1
+}
+"#
+        ),
+        vec!["error: the name `x` is defined multiple times at 3:9"]
+    );
+}
+
+#[test]
+fn functions() {
+    assert_eq!(
+        resolve(
+            r#"
+fn foo(): i32 {
+    return 1
+}
+
+fn foo(): i32 {
+    return bar()
+}
+"#
+        ),
+        vec![
+            "error: the function `foo` is defined multiple times at 6:4",
+            "error: cannot find function `bar` in this scope at 7:12",
+        ]
+    );
+}
+
+#[test]
+fn mutability() {
+    assert_eq!(
+        resolve(
+            r#"
+struct Point {
+    x: i32
+}
+
+fn main(count: i32, mut total: i32): i32 {
+    let a = 1
+    let mut b = 2
+    let arr = [1, 2]
+    let p = Point { x: 1 }
+    a = 3
+    b = 4
+    arr[0] = b
+    p.x = 5
+    count = 0
+    total = 0
+    for i in 0..10 {
+        i = i + 1
+    }
+}
+"#
+        ),
+        vec![
+            "error: cannot assign to immutable variable `a` at 11:5",
+            "error: cannot assign to immutable variable `arr` at 13:5",
+            "error: cannot assign to immutable variable `p` at 14:5",
+            "error: cannot assign to immutable variable `count` at 15:5",
+            "error: cannot assign to immutable variable `i` at 18:9",
+        ]
+    );
+
+    assert_eq!(
+        resolve("let a = [1]\nlet mut b = 0\nb += 1\na[b] -= 1\na += [2]"),
+        vec![
+            "error: cannot assign to immutable variable `a` at 4:1",
+            "error: cannot assign to immutable variable `a` at 5:1",
+        ]
+    );
+
+    let source = "let x = 1\nx = 2";
+    let tokens = tokenizer::tokenize(Cursor::new(source))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let ast = parser::parse(tokens).unwrap();
+    assert_eq!(
+        evaluator::resolve(&ast)[0].render("main.cpp2", source),
+        "error: cannot assign to immutable variable `x`
+ --> main.cpp2:2:1
+  |
+1 | let x = 1
+  |     - `x` is declared here without `mut`
+2 | x = 2
+  | ^ cannot assign to an immutable variable
+  = help: consider making this binding mutable: `mut x`
+"
+    );
+}
+
+fn resolve_with(input: &str, modules: &[(&str, &str)]) -> Vec<String> {
+    let parse = |input: &str| {
+        let tokens = tokenizer::tokenize(Cursor::new(input))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        parser::parse(tokens).unwrap()
+    };
+    let modules: Vec<evaluator::ModuleSymbols> = modules
+        .iter()
+        .map(|(name, source)| evaluator::ModuleSymbols::collect(&parse(source), name))
+        .collect();
+    evaluator::resolve_with_modules(&parse(input), &modules)
+        .iter()
+        .map(|diagnostic| diagnostic.to_string())
+        .collect()
+}
+
+#[test]
+fn namespaces() {
+    let modules = [
+        ("math", "namespace Math::Ops\nfn add(a: i32, b: i32): i32 { return a + b }"),
+        ("app", "namespace App\nfn helper(): i32 { return 1 }"),
+    ];
+    assert_eq!(
+        resolve_with(
+            r#"
+namespace App
+fn main(): i32 {
+    let a = Math::Ops::add(1, helper())
+    let b = App::run()
+    return Math::Ops::sub(a, Maths::add(b, 1))
+}
+fn run(): i32 { return 0 }
+"#,
+            &modules
+        ),
+        vec![
+            "error: cannot find function `sub` in namespace `Math::Ops` at 6:23",
+            "error: cannot find namespace `Maths` at 6:30",
+        ]
+    );
+    assert_eq!(
+        resolve_with("fn f() { }\nnamespace A", &modules),
+        vec!["error: `namespace` declarations must come first in the file at 2:1"]
+    );
+}
+
+#[test]
+fn closures() {
+    assert_eq!(
+        resolve(
+            r#"
+fn twice(x: i32): i32 { return x * 2 }
+fn main(): i32 {
+    let a = 1
+    let mut total = 0
+    let add = fn (x: i32) {
+        total = total + x + a
+        a = x
+    }
+    add(y)
+    let double = twice
+    return double(x)
+}
+"#
+        ),
+        vec![
+            "error: cannot assign to immutable variable `a` at 8:9",
+            "error: cannot find value `y` in this scope at 10:9",
+            "error: cannot find value `x` in this scope at 12:19",
+        ]
+    );
+}
@@ -1,11 +1,14 @@
 use rust::tokenizer;
+use rust::tokenizer::NumberValue;
 use rust::tokenizer::Position;
 use rust::tokenizer::Token;
 use rust::tokenizer::TokenInfo;
-use std::io::Cursor;
+use rust::tokenizer::Trivia;
+use rust::tokenizer::TriviaKind;
+use std::io::{BufRead, BufReader, Cursor, Read};
 
 fn tokenize_and_compare(input: &str, expected_output: &[TokenInfo]) -> bool {
-    match tokenizer::tokenize(Cursor::new(input)) {
+    match tokenizer::tokenize(Cursor::new(input)).collect::<Result<Vec<_>, _>>() {
         Ok(tokens) => {
             let result = tokens.iter().eq(expected_output);
             if !result {
@@ -34,37 +37,51 @@ fn bitwise() {
             TokenInfo {
                 token: Token::BwAnd,
                 lexeme: String::from("&"),
-                start_position: Position { row: 1, col: 1 }
+                start_position: Position::new(1, 1, 0),
+                end_position: Position::new(1, 2, 1),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::BwOr,
                 lexeme: String::from("|"),
-                start_position: Position { row: 2, col: 1 }
+                start_position: Position::new(2, 1, 2),
+                end_position: Position::new(2, 2, 3),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::BwXor,
                 lexeme: String::from("^"),
-                start_position: Position { row: 3, col: 1 }
+                start_position: Position::new(3, 1, 4),
+                end_position: Position::new(3, 2, 5),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::BwNot,
                 lexeme: String::from("~"),
-                start_position: Position { row: 4, col: 1 }
+                start_position: Position::new(4, 1, 6),
+                end_position: Position::new(4, 2, 7),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::BwShl,
                 lexeme: String::from("<<"),
-                start_position: Position { row: 5, col: 1 }
+                start_position: Position::new(5, 1, 8),
+                end_position: Position::new(5, 3, 10),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::BwShr,
                 lexeme: String::from(">>"),
-                start_position: Position { row: 6, col: 1 }
+                start_position: Position::new(6, 1, 11),
+                end_position: Position::new(6, 3, 13),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::EOF,
                 lexeme: String::from(""),
-                start_position: Position { row: 6, col: 3 }
+                start_position: Position::new(6, 3, 13),
+                end_position: Position::new(6, 3, 13),
+                ..Default::default()
             }
         ]
     ));
@@ -78,17 +95,23 @@ fn logic_operators() {
             TokenInfo {
                 token: Token::And,
                 lexeme: String::from("&&"),
-                start_position: Position { row: 1, col: 1 }
+                start_position: Position::new(1, 1, 0),
+                end_position: Position::new(1, 3, 2),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::Or,
                 lexeme: String::from("||"),
-                start_position: Position { row: 2, col: 1 }
+                start_position: Position::new(2, 1, 3),
+                end_position: Position::new(2, 3, 5),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::EOF,
                 lexeme: String::from(""),
-                start_position: Position { row: 2, col: 3 }
+                start_position: Position::new(2, 3, 5),
+                end_position: Position::new(2, 3, 5),
+                ..Default::default()
             }
         ]
     ));
@@ -102,12 +125,16 @@ fn typenames() {
             TokenInfo {
                 token: Token::Identifier,
                 lexeme: String::from("u16"),
-                start_position: Position { row: 1, col: 1 }
+                start_position: Position::new(1, 1, 0),
+                end_position: Position::new(1, 4, 3),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::EOF,
                 lexeme: String::from(""),
-                start_position: Position { row: 1, col: 4 }
+                start_position: Position::new(1, 4, 3),
+                end_position: Position::new(1, 4, 3),
+                ..Default::default()
             }
         ]
     ));
@@ -118,27 +145,37 @@ fn typenames() {
             TokenInfo {
                 token: Token::Identifier,
                 lexeme: String::from("Array"),
-                start_position: Position { row: 1, col: 1 }
+                start_position: Position::new(1, 1, 0),
+                end_position: Position::new(1, 6, 5),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::LowerThan,
                 lexeme: String::from("<"),
-                start_position: Position { row: 1, col: 6 }
+                start_position: Position::new(1, 6, 5),
+                end_position: Position::new(1, 7, 6),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::Identifier,
                 lexeme: String::from("u16"),
-                start_position: Position { row: 1, col: 7 }
+                start_position: Position::new(1, 7, 6),
+                end_position: Position::new(1, 10, 9),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::GreaterThan,
                 lexeme: String::from(">"),
-                start_position: Position { row: 1, col: 10 }
+                start_position: Position::new(1, 10, 9),
+                end_position: Position::new(1, 11, 10),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::EOF,
                 lexeme: String::from(""),
-                start_position: Position { row: 1, col: 11 }
+                start_position: Position::new(1, 11, 10),
+                end_position: Position::new(1, 11, 10),
+                ..Default::default()
             }
         ]
     ));
@@ -149,22 +186,30 @@ fn typenames() {
             TokenInfo {
                 token: Token::Identifier,
                 lexeme: String::from("u16"),
-                start_position: Position { row: 1, col: 1 }
+                start_position: Position::new(1, 1, 0),
+                end_position: Position::new(1, 4, 3),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::Identifier,
                 lexeme: String::from("u64"),
-                start_position: Position { row: 2, col: 1 }
+                start_position: Position::new(2, 1, 4),
+                end_position: Position::new(2, 4, 7),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::Identifier,
                 lexeme: String::from("u32"),
-                start_position: Position { row: 2, col: 5 }
+                start_position: Position::new(2, 5, 8),
+                end_position: Position::new(2, 8, 11),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::EOF,
                 lexeme: String::from(""),
-                start_position: Position { row: 2, col: 8 }
+                start_position: Position::new(2, 8, 11),
+                end_position: Position::new(2, 8, 11),
+                ..Default::default()
             }
         ]
     ));
@@ -178,17 +223,23 @@ fn strings() {
             TokenInfo {
                 token: Token::String,
                 lexeme: String::from("\"str\""),
-                start_position: Position { row: 1, col: 1 }
+                start_position: Position::new(1, 1, 0),
+                end_position: Position::new(1, 6, 5),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::String,
                 lexeme: String::from("\"tt\""),
-                start_position: Position { row: 2, col: 1 }
+                start_position: Position::new(2, 1, 6),
+                end_position: Position::new(2, 5, 10),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::EOF,
                 lexeme: String::from(""),
-                start_position: Position { row: 2, col: 5 }
+                start_position: Position::new(2, 5, 10),
+                end_position: Position::new(2, 5, 10),
+                ..Default::default()
             }
         ]
     ));
@@ -198,17 +249,23 @@ fn strings() {
             TokenInfo {
                 token: Token::String,
                 lexeme: String::from("\"str\""),
-                start_position: Position { row: 1, col: 1 }
+                start_position: Position::new(1, 1, 0),
+                end_position: Position::new(1, 6, 5),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::Identifier,
                 lexeme: String::from("num"),
-                start_position: Position { row: 2, col: 1 }
+                start_position: Position::new(2, 1, 6),
+                end_position: Position::new(2, 4, 9),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::EOF,
                 lexeme: String::from(""),
-                start_position: Position { row: 2, col: 4 }
+                start_position: Position::new(2, 4, 9),
+                end_position: Position::new(2, 4, 9),
+                ..Default::default()
             }
         ]
     ));
@@ -218,17 +275,23 @@ fn strings() {
             TokenInfo {
                 token: Token::String,
                 lexeme: String::from("\"err\""),
-                start_position: Position { row: 1, col: 1 }
+                start_position: Position::new(1, 1, 0),
+                end_position: Position::new(1, 6, 0),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::Identifier,
                 lexeme: String::from("num"),
-                start_position: Position { row: 2, col: 1 }
+                start_position: Position::new(2, 1, 5),
+                end_position: Position::new(2, 4, 8),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::EOF,
                 lexeme: String::from(""),
-                start_position: Position { row: 2, col: 4 }
+                start_position: Position::new(2, 4, 8),
+                end_position: Position::new(2, 4, 8),
+                ..Default::default()
             }
         ]
     ));
@@ -242,22 +305,30 @@ fn operators() {
             TokenInfo {
                 token: Token::Equals,
                 lexeme: String::from("=="),
-                start_position: Position { row: 1, col: 1 }
+                start_position: Position::new(1, 1, 0),
+                end_position: Position::new(1, 3, 2),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::Assignment,
                 lexeme: String::from("="),
-                start_position: Position { row: 2, col: 1 }
+                start_position: Position::new(2, 1, 3),
+                end_position: Position::new(2, 2, 4),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::Star,
                 lexeme: String::from("*"),
-                start_position: Position { row: 2, col: 3 }
+                start_position: Position::new(2, 3, 5),
+                end_position: Position::new(2, 4, 6),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::EOF,
                 lexeme: String::from(""),
-                start_position: Position { row: 2, col: 4 }
+                start_position: Position::new(2, 4, 6),
+                end_position: Position::new(2, 4, 6),
+                ..Default::default()
             }
         ]
     ));
@@ -271,17 +342,23 @@ fn assignments() {
             TokenInfo {
                 token: Token::Let,
                 lexeme: String::from("let"),
-                start_position: Position { row: 1, col: 1 }
+                start_position: Position::new(1, 1, 0),
+                end_position: Position::new(1, 4, 3),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::Mut,
                 lexeme: String::from("mut"),
-                start_position: Position { row: 2, col: 1 }
+                start_position: Position::new(2, 1, 4),
+                end_position: Position::new(2, 4, 7),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::EOF,
                 lexeme: String::from(""),
-                start_position: Position { row: 2, col: 4 }
+                start_position: Position::new(2, 4, 7),
+                end_position: Position::new(2, 4, 7),
+                ..Default::default()
             }
         ]
     ));
@@ -295,27 +372,37 @@ fn identifiers() {
             TokenInfo {
                 token: Token::Identifier,
                 lexeme: String::from("let_"),
-                start_position: Position { row: 1, col: 1 }
+                start_position: Position::new(1, 1, 0),
+                end_position: Position::new(1, 5, 4),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::Identifier,
                 lexeme: String::from("i_2"),
-                start_position: Position { row: 2, col: 1 }
+                start_position: Position::new(2, 1, 5),
+                end_position: Position::new(2, 4, 8),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::Identifier,
                 lexeme: String::from("f22nn"),
-                start_position: Position { row: 3, col: 1 }
+                start_position: Position::new(3, 1, 9),
+                end_position: Position::new(3, 6, 14),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::Identifier,
                 lexeme: String::from("_KSs12"),
-                start_position: Position { row: 4, col: 1 }
+                start_position: Position::new(4, 1, 15),
+                end_position: Position::new(4, 7, 21),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::EOF,
                 lexeme: String::from(""),
-                start_position: Position { row: 4, col: 7 }
+                start_position: Position::new(4, 7, 21),
+                end_position: Position::new(4, 7, 21),
+                ..Default::default()
             }
         ]
     ));
@@ -326,12 +413,16 @@ fn identifiers() {
             TokenInfo {
                 token: Token::Identifier,
                 lexeme: String::from("1let_"),
-                start_position: Position { row: 1, col: 1 }
+                start_position: Position::new(1, 1, 0),
+                end_position: Position::new(1, 6, 5),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::EOF,
                 lexeme: String::from(""),
-                start_position: Position { row: 1, col: 6 }
+                start_position: Position::new(1, 6, 5),
+                end_position: Position::new(1, 6, 5),
+                ..Default::default()
             }
         ]
     ));
@@ -342,12 +433,16 @@ fn identifiers() {
             TokenInfo {
                 token: Token::Identifier,
                 lexeme: String::from("%let_"),
-                start_position: Position { row: 1, col: 1 }
+                start_position: Position::new(1, 1, 0),
+                end_position: Position::new(1, 6, 5),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::EOF,
                 lexeme: String::from(""),
-                start_position: Position { row: 1, col: 6 }
+                start_position: Position::new(1, 6, 5),
+                end_position: Position::new(1, 6, 5),
+                ..Default::default()
             }
         ]
     ));
@@ -361,22 +456,30 @@ fn braces() {
             TokenInfo {
                 token: Token::LeftBraces,
                 lexeme: String::from("{"),
-                start_position: Position { row: 1, col: 1 }
+                start_position: Position::new(1, 1, 0),
+                end_position: Position::new(1, 2, 1),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::Identifier,
                 lexeme: String::from("identifier"),
-                start_position: Position { row: 1, col: 2 }
+                start_position: Position::new(1, 2, 1),
+                end_position: Position::new(1, 12, 11),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::RightBraces,
                 lexeme: String::from("}"),
-                start_position: Position { row: 1, col: 12 }
+                start_position: Position::new(1, 12, 11),
+                end_position: Position::new(1, 13, 12),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::EOF,
                 lexeme: String::from(""),
-                start_position: Position { row: 1, col: 13 }
+                start_position: Position::new(1, 13, 12),
+                end_position: Position::new(1, 13, 12),
+                ..Default::default()
             }
         ]
     ));
@@ -387,12 +490,16 @@ fn braces() {
             TokenInfo {
                 token: Token::Identifier,
                 lexeme: String::from("1let_"),
-                start_position: Position { row: 1, col: 1 }
+                start_position: Position::new(1, 1, 0),
+                end_position: Position::new(1, 6, 5),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::EOF,
                 lexeme: String::from(""),
-                start_position: Position { row: 4, col: 7 }
+                start_position: Position::new(4, 7, 0),
+                end_position: Position::new(4, 7, 0),
+                ..Default::default()
             }
         ]
     ));
@@ -403,12 +510,16 @@ fn braces() {
             TokenInfo {
                 token: Token::Identifier,
                 lexeme: String::from("%let_"),
-                start_position: Position { row: 1, col: 1 }
+                start_position: Position::new(1, 1, 0),
+                end_position: Position::new(1, 6, 5),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::EOF,
                 lexeme: String::from(""),
-                start_position: Position { row: 1, col: 6 }
+                start_position: Position::new(1, 6, 5),
+                end_position: Position::new(1, 6, 5),
+                ..Default::default()
             }
         ]
     ));
@@ -424,57 +535,79 @@ fn functions() {
             TokenInfo {
                 token: Token::Fn,
                 lexeme: String::from("fn"),
-                start_position: Position { row: 1, col: 1 }
+                start_position: Position::new(1, 1, 0),
+                end_position: Position::new(1, 3, 2),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::Identifier,
                 lexeme: String::from("main"),
-                start_position: Position { row: 1, col: 4 }
+                start_position: Position::new(1, 4, 3),
+                end_position: Position::new(1, 8, 7),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::LeftParantheses,
                 lexeme: String::from("("),
-                start_position: Position { row: 1, col: 8 }
+                start_position: Position::new(1, 8, 7),
+                end_position: Position::new(1, 9, 8),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::RightParantheses,
                 lexeme: String::from(")"),
-                start_position: Position { row: 1, col: 9 }
+                start_position: Position::new(1, 9, 8),
+                end_position: Position::new(1, 10, 9),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::Colon,
                 lexeme: String::from(":"),
-                start_position: Position { row: 1, col: 10 }
+                start_position: Position::new(1, 10, 9),
+                end_position: Position::new(1, 11, 10),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::Identifier,
                 lexeme: String::from("u32"),
-                start_position: Position { row: 1, col: 12 }
+                start_position: Position::new(1, 12, 11),
+                end_position: Position::new(1, 15, 14),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::LeftBraces,
                 lexeme: String::from("{"),
-                start_position: Position { row: 1, col: 16 }
+                start_position: Position::new(1, 16, 15),
+                end_position: Position::new(1, 17, 16),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::Return,
                 lexeme: String::from("return"),
-                start_position: Position { row: 2, col: 5 }
+                start_position: Position::new(2, 5, 21),
+                end_position: Position::new(2, 11, 27),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::Number,
                 lexeme: String::from("30"),
-                start_position: Position { row: 2, col: 12 }
+                start_position: Position::new(2, 12, 28),
+                end_position: Position::new(2, 14, 30),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::RightBraces,
                 lexeme: String::from("}"),
-                start_position: Position { row: 3, col: 1 }
+                start_position: Position::new(3, 1, 31),
+                end_position: Position::new(3, 2, 32),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::EOF,
                 lexeme: String::from(""),
-                start_position: Position { row: 3, col: 2 }
+                start_position: Position::new(3, 2, 32),
+                end_position: Position::new(3, 2, 32),
+                ..Default::default()
             }
         ]
     ));
@@ -485,57 +618,79 @@ fn functions() {
             TokenInfo {
                 token: Token::Fn,
                 lexeme: String::from("fn"),
-                start_position: Position { row: 1, col: 1 }
+                start_position: Position::new(1, 1, 0),
+                end_position: Position::new(1, 3, 2),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::Identifier,
                 lexeme: String::from("main"),
-                start_position: Position { row: 1, col: 4 }
+                start_position: Position::new(1, 4, 3),
+                end_position: Position::new(1, 8, 7),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::LeftParantheses,
                 lexeme: String::from("("),
-                start_position: Position { row: 1, col: 8 }
+                start_position: Position::new(1, 8, 7),
+                end_position: Position::new(1, 9, 8),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::RightParantheses,
                 lexeme: String::from(")"),
-                start_position: Position { row: 1, col: 9 }
+                start_position: Position::new(1, 9, 8),
+                end_position: Position::new(1, 10, 9),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::Colon,
                 lexeme: String::from(":"),
-                start_position: Position { row: 1, col: 10 }
+                start_position: Position::new(1, 10, 9),
+                end_position: Position::new(1, 11, 10),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::Identifier,
                 lexeme: String::from("u32"),
-                start_position: Position { row: 1, col: 11 }
+                start_position: Position::new(1, 11, 10),
+                end_position: Position::new(1, 14, 13),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::LeftBraces,
                 lexeme: String::from("{"),
-                start_position: Position { row: 1, col: 14 }
+                start_position: Position::new(1, 14, 13),
+                end_position: Position::new(1, 15, 14),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::Return,
                 lexeme: String::from("return"),
-                start_position: Position { row: 1, col: 15 }
+                start_position: Position::new(1, 15, 14),
+                end_position: Position::new(1, 21, 20),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::Number,
                 lexeme: String::from("30"),
-                start_position: Position { row: 1, col: 22 }
+                start_position: Position::new(1, 22, 21),
+                end_position: Position::new(1, 24, 23),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::RightBraces,
                 lexeme: String::from("}"),
-                start_position: Position { row: 1, col: 24 }
+                start_position: Position::new(1, 24, 23),
+                end_position: Position::new(1, 25, 24),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::EOF,
                 lexeme: String::from(""),
-                start_position: Position { row: 1, col: 25 }
+                start_position: Position::new(1, 25, 24),
+                end_position: Position::new(1, 25, 24),
+                ..Default::default()
             }
         ]
     ));
@@ -549,23 +704,400 @@ fn namespaces() {
             TokenInfo {
                 token: Token::Std,
                 lexeme: String::from("std"),
-                start_position: Position { row: 1, col: 1 }
+                start_position: Position::new(1, 1, 0),
+                end_position: Position::new(1, 4, 3),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::DoubleColon,
                 lexeme: String::from("::"),
-                start_position: Position { row: 1, col: 4 }
+                start_position: Position::new(1, 4, 3),
+                end_position: Position::new(1, 6, 5),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::Identifier,
                 lexeme: String::from("whilep"),
-                start_position: Position { row: 1, col: 6 }
+                start_position: Position::new(1, 6, 5),
+                end_position: Position::new(1, 12, 11),
+                ..Default::default()
             },
             TokenInfo {
                 token: Token::EOF,
                 lexeme: String::from(""),
-                start_position: Position { row: 1, col: 12 }
+                start_position: Position::new(1, 12, 11),
+                end_position: Position::new(1, 12, 11),
+                ..Default::default()
             }
         ]
     ));
 }
+
+#[test]
+fn comments() {
+    let tokens = tokenizer::tokenize(Cursor::new(
+        "/// doc\nlet a = 1 // one\n/* outer /* inner */ still outer */ a / 2\n// last",
+    ))
+    .collect::<Result<Vec<_>, _>>()
+    .unwrap();
+    let lexemes: Vec<&str> = tokens.iter().map(|token| token.lexeme.as_str()).collect();
+    assert_eq!(lexemes, vec!["let", "a", "=", "1", "a", "/", "2", ""]);
+
+    assert_eq!(
+        tokens[0].leading_trivia,
+        vec![Trivia {
+            kind: TriviaKind::DocComment,
+            text: String::from("/// doc"),
+            start_position: Position::new(1, 1, 0),
+            end_position: Position::new(1, 8, 7),
+        }]
+    );
+    assert_eq!(
+        tokens[3].trailing_trivia,
+        vec![Trivia {
+            kind: TriviaKind::LineComment,
+            text: String::from("// one"),
+            start_position: Position::new(2, 11, 18),
+            end_position: Position::new(2, 17, 24),
+        }]
+    );
+    assert_eq!(
+        tokens[4].leading_trivia,
+        vec![Trivia {
+            kind: TriviaKind::BlockComment,
+            text: String::from("/* outer /* inner */ still outer */"),
+            start_position: Position::new(3, 1, 25),
+            end_position: Position::new(3, 36, 60),
+        }]
+    );
+    assert_eq!(tokens[4].start_position, Position::new(3, 37, 61));
+    assert_eq!(tokens[7].token, Token::EOF);
+    assert_eq!(tokens[7].leading_trivia[0].text, "// last");
+
+    assert!(matches!(
+        tokenizer::tokenize(Cursor::new("let a /* /* */ = 1")).collect::<Result<Vec<_>, _>>(),
+        Err(tokenizer::Error::UnclosedComment(Position {
+            row: 1,
+            col: 7,
+            ..
+        }))
+    ));
+}
+
+#[test]
+fn string_literals() {
+    let tokens = tokenizer::tokenize(Cursor::new(
+        r###""say \"hi\"\n" r"C:\dir" r#"a "quoted" b"# 'c' '\''"###,
+    ))
+    .collect::<Result<Vec<_>, _>>()
+    .unwrap();
+    let literals: Vec<(Token, String)> = tokens
+        .iter()
+        .filter(|token| token.token != Token::EOF)
+        .map(|token| (token.token, tokenizer::literal_value(&token.lexeme)))
+        .collect();
+    assert_eq!(
+        literals,
+        vec![
+            (Token::String, String::from("say \"hi\"\n")),
+            (Token::String, String::from(r"C:\dir")),
+            (Token::String, String::from(r#"a "quoted" b"#)),
+            (Token::Char, String::from("c")),
+            (Token::Char, String::from("'")),
+        ]
+    );
+    assert_eq!(tokens[2].end_position, Position::new(1, 43, 42));
+    assert_eq!(
+        tokenizer::literal_value(r#""\u{48}\u{1F600}\t\\""#),
+        "H\u{1F600}\t\\"
+    );
+
+    assert!(matches!(
+        tokenizer::tokenize(Cursor::new(r#"let s = "a\qb""#)).collect::<Result<Vec<_>, _>>(),
+        Err(tokenizer::Error::InvalidEscape(sequence, Position { row: 1, col: 11, .. })) if sequence == r"\q"
+    ));
+    assert!(matches!(
+        tokenizer::tokenize(Cursor::new(r#""\u{110000}""#)).collect::<Result<Vec<_>, _>>(),
+        Err(tokenizer::Error::InvalidEscape(sequence, _)) if sequence == r"\u{110000}"
+    ));
+    assert!(matches!(
+        tokenizer::tokenize(Cursor::new("'ab'")).collect::<Result<Vec<_>, _>>(),
+        Err(tokenizer::Error::InvalidChar(
+            _,
+            Position { row: 1, col: 1, .. }
+        ))
+    ));
+    assert!(matches!(
+        tokenizer::tokenize(Cursor::new(r#"r#"open"#)).collect::<Result<Vec<_>, _>>(),
+        Err(tokenizer::Error::UnclosedString(_))
+    ));
+    assert!(matches!(
+        tokenizer::tokenize(Cursor::new(r#""open\""#)).collect::<Result<Vec<_>, _>>(),
+        Err(tokenizer::Error::UnclosedString(_))
+    ));
+}
+
+#[test]
+fn number_literals() {
+    let tokens = tokenizer::tokenize(Cursor::new(
+        "2.75 1e-3 2.5E+2 0xff 0b1010 0o17 1_000_000 10u8 2.0f32 7f64 0..3",
+    ))
+    .collect::<Result<Vec<_>, _>>()
+    .unwrap();
+    let literals: Vec<&str> = tokens.iter().map(|token| token.lexeme.as_str()).collect();
+    assert_eq!(
+        literals,
+        vec![
+            "2.75",
+            "1e-3",
+            "2.5E+2",
+            "0xff",
+            "0b1010",
+            "0o17",
+            "1_000_000",
+            "10u8",
+            "2.0f32",
+            "7f64",
+            "0",
+            "..",
+            "3",
+            ""
+        ]
+    );
+
+    let values: Vec<_> = tokens[..10]
+        .iter()
+        .map(|token| tokenizer::number_literal(&token.lexeme).unwrap())
+        .collect();
+    assert_eq!(
+        values,
+        vec![
+            (NumberValue::Float(2.75), None),
+            (NumberValue::Float(0.001), None),
+            (NumberValue::Float(250.0), None),
+            (NumberValue::Int(255), None),
+            (NumberValue::Int(10), None),
+            (NumberValue::Int(15), None),
+            (NumberValue::Int(1_000_000), None),
+            (NumberValue::Int(10), Some("u8")),
+            (NumberValue::Float(2.0), Some("f32")),
+            (NumberValue::Float(7.0), Some("f64")),
+        ]
+    );
+    assert_eq!(
+        tokenizer::number_literal("0x1i64"),
+        Some((NumberValue::Int(1), Some("i64")))
+    );
+    assert_eq!(
+        tokenizer::number_literal("128i8"),
+        Some((NumberValue::Int(128), Some("i8")))
+    );
+
+    for invalid in ["256u8", "1.5u32", "0b102", "0xg", "12abc", "1e"] {
+        assert!(
+            matches!(
+                tokenizer::tokenize(Cursor::new(invalid)).collect::<Result<Vec<_>, _>>(),
+                Err(tokenizer::Error::InvalidNumber(
+                    _,
+                    Position { row: 1, col: 1, .. }
+                ))
+            ),
+            "{}",
+            invalid
+        );
+    }
+}
+
+#[test]
+fn unicode() {
+    let tokens = tokenizer::tokenize(Cursor::new("let größe = \"héllo 😀\" // ü\nπ"))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let lexemes: Vec<&str> = tokens.iter().map(|token| token.lexeme.as_str()).collect();
+    assert_eq!(lexemes, vec!["let", "größe", "=", "\"héllo 😀\"", "π", ""]);
+    assert_eq!(tokens[1].token, Token::Identifier);
+    assert_eq!(tokens[1].end_position, Position::new(1, 10, 11));
+    assert_eq!(tokens[3].start_position, Position::new(1, 13, 14));
+    assert_eq!(tokens[3].end_position, Position::new(1, 22, 27));
+    assert_eq!(tokens[3].trailing_trivia[0].text, "// ü");
+    assert_eq!(tokens[4].start_position, Position::new(2, 1, 34));
+    assert_eq!(tokenizer::literal_value(&tokens[3].lexeme), "héllo 😀");
+
+    assert!(matches!(
+        tokenizer::tokenize(Cursor::new(&b"let a = \"\xff\""[..])).collect::<Result<Vec<_>, _>>(),
+        Err(tokenizer::Error::InvalidUtf8(Position {
+            row: 1,
+            col: 10,
+            ..
+        }))
+    ));
+    assert!(matches!(
+        tokenizer::tokenize(Cursor::new("let € = 1")).collect::<Result<Vec<_>, _>>(),
+        Err(tokenizer::Error::InvalidPattern(
+            _,
+            Position { row: 1, col: 5, .. }
+        ))
+    ));
+}
+
+#[test]
+fn streaming() {
+    struct Endless;
+
+    impl Read for Endless {
+        fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+            let line = b"let a = 1\n";
+            let len = buffer.len().min(line.len());
+            buffer[..len].copy_from_slice(&line[..len]);
+            Ok(len)
+        }
+    }
+
+    let lexemes: Vec<String> = tokenizer::tokenize(BufReader::new(Endless))
+        .take(5)
+        .map(|token| token.unwrap().lexeme)
+        .collect();
+    assert_eq!(lexemes, vec!["let", "a", "=", "1", "let"]);
+
+    struct Failing;
+
+    impl Read for Failing {
+        fn read(&mut self, _buffer: &mut [u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::other("disk on fire"))
+        }
+    }
+
+    let mut tokens = tokenizer::tokenize(BufReader::new(Cursor::new("let a").chain(Failing)));
+    assert_eq!(tokens.next().unwrap().unwrap().lexeme, "let");
+    assert!(matches!(
+        tokens.next(),
+        Some(Err(tokenizer::Error::InvalidStream))
+    ));
+    assert!(tokens.next().is_none());
+
+    // Serves two bytes at a time from its buffer and never reads by itself,
+    // so that characters straddle the end of the buffer.
+    struct Chunked(&'static [u8]);
+
+    impl Read for Chunked {
+        fn read(&mut self, _buffer: &mut [u8]) -> std::io::Result<usize> {
+            panic!("the tokenizer reads through fill_buf")
+        }
+    }
+
+    impl BufRead for Chunked {
+        fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+            Ok(&self.0[..self.0.len().min(2)])
+        }
+
+        fn consume(&mut self, amount: usize) {
+            self.0 = &self.0[amount..];
+        }
+    }
+
+    let lexemes: Vec<String> = tokenizer::tokenize(Chunked("let äöü = \"€\"".as_bytes()))
+        .map(|token| token.unwrap().lexeme)
+        .collect();
+    assert_eq!(lexemes, vec!["let", "äöü", "=", "\"€\"", ""]);
+}
+
+#[test]
+fn comparison_operators() {
+    let tokens: Vec<Token> = tokenizer::tokenize(Cursor::new("a <= b >= c != !d % e"))
+        .map(|token| token.unwrap().token)
+        .collect();
+    assert_eq!(
+        tokens,
+        [
+            Token::Identifier,
+            Token::LowerEquals,
+            Token::Identifier,
+            Token::GreaterEquals,
+            Token::Identifier,
+            Token::Inequal,
+            Token::Not,
+            Token::Identifier,
+            Token::Modulo,
+            Token::Identifier,
+            Token::EOF,
+        ]
+    );
+}
+
+#[test]
+fn booleans() {
+    let tokens: Vec<(Token, String)> = tokenizer::tokenize(Cursor::new("true false truth"))
+        .map(|token| token.unwrap())
+        .map(|token| (token.token, token.lexeme))
+        .collect();
+    assert_eq!(
+        tokens,
+        [
+            (Token::True, String::from("true")),
+            (Token::False, String::from("false")),
+            (Token::Identifier, String::from("truth")),
+            (Token::EOF, String::new()),
+        ]
+    );
+}
+
+#[test]
+fn semicolons() {
+    let tokens: Vec<Token> = tokenizer::tokenize(Cursor::new("a;b ;;"))
+        .map(|token| token.unwrap().token)
+        .collect();
+    assert_eq!(
+        tokens,
+        [
+            Token::Identifier,
+            Token::Semicolon,
+            Token::Identifier,
+            Token::Semicolon,
+            Token::Semicolon,
+            Token::EOF,
+        ]
+    );
+}
+
+#[test]
+fn compound_assignments() {
+    let tokens: Vec<Token> =
+        tokenizer::tokenize(Cursor::new("+= -= *= /= %= &= |= ^= <<= >>= <= >="))
+            .map(|token| token.unwrap().token)
+            .collect();
+    assert_eq!(
+        tokens,
+        [
+            Token::AdditionAssignment,
+            Token::SubtractionAssignment,
+            Token::StarAssignment,
+            Token::DivisionAssignment,
+            Token::ModuloAssignment,
+            Token::BwAndAssignment,
+            Token::BwOrAssignment,
+            Token::BwXorAssignment,
+            Token::BwShlAssignment,
+            Token::BwShrAssignment,
+            Token::LowerEquals,
+            Token::GreaterEquals,
+            Token::EOF,
+        ]
+    );
+}
+
+#[test]
+fn namespace_declarations() {
+    let tokens: Vec<Token> = tokenizer::tokenize(Cursor::new("namespace A::B"))
+        .map(|token| token.unwrap().token)
+        .collect();
+    assert_eq!(
+        tokens,
+        [
+            Token::Namespace,
+            Token::Identifier,
+            Token::DoubleColon,
+            Token::Identifier,
+            Token::EOF,
+        ]
+    );
+}
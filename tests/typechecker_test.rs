@@ -0,0 +1,323 @@
+use rust::evaluator;
+use rust::parser;
+use rust::tokenizer;
+use std::io::Cursor;
+
+fn check(input: &str) -> Vec<String> {
+    let tokens = tokenizer::tokenize(Cursor::new(input))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    match parser::parse(tokens) {
+        Ok(ast) => evaluator::check(&ast)
+            .iter()
+            .map(|diagnostic| diagnostic.to_string())
+            .collect(),
+        Err(errors) => {
+            for error in errors {
+                println!("{}", error);
+            }
+            panic!("failed to parse");
+        }
+    }
+}
+
+fn check_and_transpile(input: &str) -> String {
+    let tokens = tokenizer::tokenize(Cursor::new(input))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let ast = parser::parse(tokens).unwrap();
+    let diagnostics = evaluator::check(&ast);
+    assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+    evaluator::cpptranspile(&ast)
+}
+
+#[test]
+fn well_typed() {
+    assert_eq!(
+        check(
+            r#"
+struct Point {
+    x: i32
+    y: i32
+}
+
+fn length(p: Point): i64 {
+    let sum: i64 = p.x * p.x + p.y * p.y
+    return sum
+}
+
+fn main(): i32 {
+    let mut arr: Array<u8> = [5, 6, 7]
+    arr[0] = 1
+    let grid: Array<Array<u8>> = [arr, [1]]
+    let row: Array<u8> = grid[0]
+    let name: String = "a" + "b"
+    let p = Point { x: 1, y: -2 }
+    let l = length(p)
+    for i in 0..3 {
+        std::println("{}", arr[i])
+    }
+    return 0
+}
+"#
+        ),
+        Vec::<String>::new()
+    );
+}
+
+#[test]
+fn mismatches() {
+    assert_eq!(
+        check(r#"let x = "a" + 1"#),
+        vec!["error: cannot add `i32` to `String` at 1:13"]
+    );
+    assert_eq!(
+        check(
+            r#"
+fn name(): String {
+    return 10 * 2
+}
+
+fn main(): i32 {
+    let s: String = name()
+    return s
+}
+"#
+        ),
+        vec![
+            "error: mismatched types at 3:12",
+            "error: mismatched types at 8:12",
+        ]
+    );
+    assert_eq!(
+        check(
+            r#"
+struct Point {
+    x: i32
+}
+fn take(p: Point, i: i32): i32 {
+    return i
+}
+let p = Point { x: "one" }
+let a = take(p)
+let b = take(1, p)
+let c = p.y
+"#
+        ),
+        vec![
+            "error: mismatched types at 8:20",
+            "error: this function takes 2 arguments but 1 were supplied at 9:9",
+            "error: mismatched types at 10:14",
+            "error: mismatched types at 10:17",
+            "error: no field `y` on type `Point` at 11:11",
+        ]
+    );
+}
+
+#[test]
+fn inferred_declarations() {
+    assert_eq!(
+        check_and_transpile(
+            r#"
+struct Point {
+    x: i64
+}
+let a = 10
+let b: u64 = 3
+let c = b * 2
+let s = "text"
+let arr = [a, 2]
+let p = Point { x: 1 }
+let x = p.x
+"#
+        ),
+        "#include <cstdint>
+
+#include <string>
+
+#include <vector>
+struct Point { int64_t x; };const int32_t a = 10;const uint64_t b = 3;const uint64_t c = b * 2;const std::string s = \"text\";const std::vector<int32_t> arr = { a, 2 };const Point p = Point{ .x = 1 };const int64_t x = p.x;"
+    );
+}
+
+#[test]
+fn number_literals() {
+    assert_eq!(
+        check(
+            r#"
+let a: f32 = 1.5
+let b: u8 = 0xff
+let c = 2.0f32 * 3
+let d: f32 = c
+let e = 1 + 2.5
+let f: i32 = e
+let g: u8 = 10u16
+"#
+        ),
+        vec![
+            "error: mismatched types at 7:14",
+            "error: mismatched types at 8:13",
+        ]
+    );
+    assert_eq!(
+        check_and_transpile("let x = 1_000u64\nlet y = -0.5f32\nlet z = 0o17 + 0b11"),
+        "#include <cstdint>
+const uint64_t x = uint64_t{1000};const float y = -0.5f;const int32_t z = 017 + 0b11;"
+    );
+    assert_eq!(
+        check("let a = 128i8\nlet b = -128i8"),
+        vec!["error: literal out of range for `i8` at 1:9"]
+    );
+    assert_eq!(
+        check_and_transpile("let x = -128i8\nlet y = -9223372036854775808i64\nlet z = -127i8"),
+        "#include <cstdint>
+const int8_t x = INT8_MIN;const int64_t y = INT64_MIN;const int8_t z = int8_t{-127};"
+    );
+}
+
+#[test]
+fn operators() {
+    assert_eq!(
+        check(
+            r#"
+let a = 7 % 2 << 1
+let b = 1 < 2 && !(a >= 3)
+let c = a && b
+let d = 1.5 << 2
+let e = !a
+"#
+        ),
+        vec![
+            "error: cannot apply `&&` to `i32` and `bool` at 4:11",
+            "error: cannot apply `<<` to `f64` and `i32` at 5:13",
+            "error: cannot apply unary operator `!` to type `i32` at 6:9",
+        ]
+    );
+    assert_eq!(
+        check("let c = 'a'\nlet b = c < 'b' && \"a\" >= \"b\"\nlet d = c < \"b\""),
+        vec!["error: cannot apply `<` to `char` and `String` at 3:11"]
+    );
+}
+
+#[test]
+fn conditions() {
+    assert_eq!(
+        check(
+            r#"
+let i = 1
+let b = true
+if b && i < 2 { i = 2 }
+if i { i = 3 }
+while i - 1 { i = 0 }
+let c: bool = 0
+"#
+        ),
+        vec![
+            "error: mismatched types at 5:4",
+            "error: mismatched types at 6:7",
+            "error: mismatched types at 7:15",
+        ]
+    );
+}
+
+#[test]
+fn compound_assignments() {
+    assert_eq!(
+        check(
+            r#"
+let mut x: u8 = 1
+x += 1
+x <<= 2
+x += 1.5
+let mut s = "a"
+s += "b"
+s -= "b"
+"#
+        ),
+        vec![
+            "error: mismatched types at 5:6",
+            "error: cannot subtract `String` from `String` at 8:3",
+        ]
+    );
+}
+
+#[test]
+fn main_signature() {
+    assert_eq!(check("fn main() { }\n"), Vec::<String>::new());
+    assert_eq!(
+        check("fn main(args: Array<String>): i32 { return 0 }"),
+        Vec::<String>::new()
+    );
+    assert_eq!(
+        check("fn main(count: i32): u8 { return 0 }"),
+        vec!["error: `main` function has an invalid signature at 1:4"]
+    );
+}
+
+#[test]
+fn qualified_paths() {
+    assert_eq!(
+        check(
+            r#"
+namespace App
+fn half(x: i32): i32 { return x / 2 }
+let a: i32 = Other::value(true)
+let b: Other::Point = Other::origin()
+let c: bool = App::half(4)
+"#
+        ),
+        vec!["error: mismatched types at 6:15"]
+    );
+}
+
+#[test]
+fn generic_functions() {
+    assert_eq!(
+        check(
+            r#"
+fn first<T>(items: Array<T>, fallback: T): T { return fallback }
+fn id<T>(t: T): T { return t }
+let a: i32 = id(1)
+let b: bool = id(2)
+let c: String = id::<String>(3)
+let d = id::<i32, bool>(4)
+let e = first([1, 2], 3)
+let f = one::<i32>()
+fn one(): i32 { return 1 }
+"#
+        ),
+        vec![
+            "error: mismatched types at 5:15",
+            "error: mismatched types at 6:30",
+            "error: this function takes 1 generic arguments but 2 were supplied at 7:9",
+            "error: this function takes 0 generic arguments but 1 were supplied at 9:9",
+        ]
+    );
+    assert_eq!(
+        check_and_transpile("fn id<T>(t: T): T { let u: T = t\nreturn u }\nlet x: i64 = id::<i64>(1)"),
+        "#include <cstdint>
+template<typename T> T id(const T t) { const T u = t; return u; }const int64_t x = id<int64_t>(1);"
+    );
+}
+
+#[test]
+fn closures() {
+    assert_eq!(
+        check(
+            r#"
+fn apply(f: fn(i32): bool, x: i32): bool { return f(x) }
+fn positive(x: i32): bool { return x > 0 }
+let even = fn (i: i32): bool { return 0 == i % 2 }
+let a: bool = apply(even, 2) && apply(positive, 1)
+let b: fn(i32): i32 = even
+let c = even("2")
+let d = fn (): String { return 1 }
+"#
+        ),
+        vec![
+            "error: mismatched types at 6:23",
+            "error: mismatched types at 7:14",
+            "error: mismatched types at 8:32",
+        ]
+    );
+}